@@ -1,9 +1,40 @@
 // mod lib;
 mod rank;
+use kmn_pairs::cli;
+use kmn_pairs::progress::install_interrupt_handler;
 use rank::*;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // Ctrl-C stops the running search (and the program otherwise)
+    if let Err(err) = install_interrupt_handler() {
+        eprintln!("Ctrl-C handler not installed: {err}");
+    }
+    if args.first().is_some_and(|x| x == "replay") {
+        std::process::exit(cli::run_replay(&args[1..]));
+    }
+    let options = match cli::menu_options(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!(
+                "{err}\nusage: rank [--script FILE [--keep-going]] [--seed SEED] [--out FILE]\n       rank replay SCRIPT --expected JSON [--transcript TXT] [--seed SEED] [--record]"
+            );
+            std::process::exit(cli::EXIT_INVALID);
+        }
+    };
     println!("Hello RANK!");
     // hello();
-    rank_menu();
+    let ranking_data = rank_menu();
+    if let (Some(path), Some(ranking)) = (&options.out, &ranking_data) {
+        let written = serde_json::to_string(&SerdeRanking::from(ranking))
+            .map_err(|err| err.to_string())
+            .and_then(|out| std::fs::write(path, out + "\n").map_err(|err| err.to_string()));
+        if let Err(err) = written {
+            eprintln!("{path}: {err}");
+            std::process::exit(cli::EXIT_INVALID);
+        }
+    }
+    if options.script_mode {
+        std::process::exit(cli::script_exit_code());
+    }
 }
@@ -1,6 +1,8 @@
 // `rank`
+use kmn_pairs::history::*;
 use kmn_pairs::kmn_serde::*;
 use kmn_pairs::menu::*;
+use kmn_pairs::resize::*;
 use kmn_pairs::*;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
@@ -12,13 +14,14 @@ pub fn hello() {
 }
 */
 
-pub fn rank_menu() {
+// `rank_menu` returns the final ranking (if defined)
+pub fn rank_menu() -> Option<Ranking> {
     let mut ranking_data: Option<Ranking> = None;
     input_menu(&mut ranking_data);
-    if let Some(mut ranking) = ranking_data {
-        edit_menu(&mut ranking);
+    if let Some(ranking) = &mut ranking_data {
+        edit_menu(ranking);
         println!("\nRANKING JSON:\n");
-        match serde_json::to_string(&SerdeRanking::from(&ranking)) {
+        match serde_json::to_string(&SerdeRanking::from(&*ranking)) {
             Ok(out) => {
                 println!("{}\n", out)
             }
@@ -28,8 +31,8 @@ pub fn rank_menu() {
         }
     } else {
         println!("NO RANKING SET!");
-        return;
     }
+    ranking_data
 }
 
 fn input_menu(ranking_data: &mut Option<Ranking>) {
@@ -37,6 +40,10 @@ fn input_menu(ranking_data: &mut Option<Ranking>) {
         println!("\nDEFINE RANKING");
         println!("-> Input command (h for help): ");
         let cmd = read_line();
+        if input_closed() {
+            println!("End of input.");
+            break 'input;
+        }
         let cmd = cmd.trim();
         match cmd {
             "h" => {
@@ -81,24 +88,24 @@ fn input_menu(ranking_data: &mut Option<Ranking>) {
                                 break 'input;
                             }
                             Err(err) => {
-                                println!("{}", err);
+                                print_error(err);
                                 continue 'input; // try again!
                             }
                         }
                     }
                     Err(err) => {
-                        println!("{}", err);
+                        print_error(err);
                         continue 'input; // try again!
                     }
                 }
             }
-            _ => println!("Unknown command: {}", cmd),
+            _ => print_error(format!("Unknown command: {}", cmd)),
         }
     }
 }
 
 pub fn edit_menu(ranking: &mut Ranking) {
-    let mut rng = rand::rng(); // random number generator
+    let mut rng = menu_rng(); // random number generator
     if ranking.assignments_data.is_none() {
         println!("You have to define some assignments first!!!");
         return;
@@ -108,6 +115,7 @@ pub fn edit_menu(ranking: &mut Ranking) {
     // let (k, m, n) = ranking.assignments_data.as_ref().unwrap().get_kmn(); // unwrap should be save here
     //let p = ranking.assignments_data.as_ref().unwrap().p();
 
+    let mut history = History::new(&*ranking, HISTORY_MAX);
     'edit: loop {
         // TODO
 
@@ -129,7 +137,16 @@ pub fn edit_menu(ranking: &mut Ranking) {
         println!("-> Input command (h for help): ");
 
         let cmd = read_line();
-        let cmd = cmd.trim();
+        if input_closed() {
+            println!("End of input.");
+            break 'edit;
+        }
+        let line = cmd.trim();
+        // command with optional inline argument (e.g. `rmranker 3`)
+        let (cmd, arg) = match line.split_once(char::is_whitespace) {
+            Some((cmd, arg)) => (cmd, arg.trim()),
+            None => (line, ""),
+        };
         match cmd {
             "h" => {
                 println!(
@@ -140,6 +157,8 @@ pub fn edit_menu(ranking: &mut Ranking) {
             prankersf  print rankers' infos with forbidden
             prkrsfar   print rankers' infos with forbidden, assignments and rankings
             prkdfa     print ranked infos with forbidden and assignments
+            prcomp     print the comparison graph of ranked (ranked sharing a ranker): components, diameter,
+                       algebraic connectivity and the ranked with the weakest links
             pscores    print scores
             presults   print results
             preduced   print json reduced to ranked in some position of results
@@ -153,8 +172,14 @@ pub fn edit_menu(ranking: &mut Ranking) {
             iranked    input ranked info label
             irlvrvj    input rankings from one-line JSONs
             dr         delete rankings
+            rmranker   remove rankers (`rmranker <ids> [k=K]`), rmranked removes ranked, addranker/addranked
+                       add new ones (`addranker [count] [k=K]`): the assignments keep as many pairs as possible
+                       and the rankers keeping their ranked keep their rankings
             simid      simulate rankings, where score = id_of_ranked +/- random_dev
             simrand    simulate rankings with random scores
+            undo       undo the last command changing the ranking (rankings, labels or assignments)
+            redo       redo the undone command
+            history    list the steps of undo/redo history
             quit       quit 'EDIT RANKING' menu (prints JSON ranking data)
             "
                 );
@@ -188,6 +213,9 @@ pub fn edit_menu(ranking: &mut Ranking) {
             "prkdfa" => {
                 prkdfa(ranking);
             }
+            "prcomp" => {
+                prcomp(ranking);
+            }
             "pscores" => {
                 pscores(ranking, &vec![]);
             }
@@ -220,16 +248,19 @@ pub fn edit_menu(ranking: &mut Ranking) {
                     println!("{cmd}: Ok");
                 }
             }
+            "rmranker" | "rmranked" | "addranker" | "addranked" => {
+                resize_ranking(ranking, cmd, arg);
+            }
             "dr" => 'dr: loop {
                 println!("{cmd}: input ranker < {}", ranking.rankers.len());
                 let input = read_line();
                 match serde_json::from_str::<usize>(&input) {
                     Ok(ranker) => {
                         if ranker >= ranking.rankers.len() {
-                            println!(
+                            print_error(format!(
                                 "{cmd}: You have input ranker = {ranker} >= {} !!!",
                                 ranking.rankers.len()
-                            );
+                            ));
                         } else {
                             ranking.rankers[ranker].ranking = None;
                             println!("{cmd}: ranking of ranker {ranker} deleted!");
@@ -247,10 +278,10 @@ pub fn edit_menu(ranking: &mut Ranking) {
                 match serde_json::from_str::<usize>(&input) {
                     Ok(id) => {
                         if id >= ranking.rankers.len() {
-                            println!(
+                            print_error(format!(
                                 "{cmd}: You have input ranker's id = {id} >= {} !!!",
                                 ranking.rankers.len()
-                            );
+                            ));
                         } else {
                             println!("{cmd}: input info label for ranker {id}:");
                             let mut info = read_line();
@@ -274,10 +305,10 @@ pub fn edit_menu(ranking: &mut Ranking) {
                 match serde_json::from_str::<usize>(&input) {
                     Ok(id) => {
                         if id >= ranking.rankers.len() {
-                            println!(
+                            print_error(format!(
                                 "{cmd}: You have input ranked id = {id} >= {} !!!",
                                 ranking.ranked.len()
-                            );
+                            ));
                         } else {
                             println!("{cmd}: input info label for ranker {id}:");
                             let mut info = read_line();
@@ -311,7 +342,7 @@ pub fn edit_menu(ranking: &mut Ranking) {
                         prlvrvj(ranking);
                     }
                     Err(err) => {
-                        println!("{err}");
+                        print_error(err);
                     }
                 }
             }
@@ -323,15 +354,47 @@ pub fn edit_menu(ranking: &mut Ranking) {
                         prlvrvj(ranking);
                     }
                     Err(err) => {
-                        println!("{err}");
+                        print_error(err);
+                    }
+                }
+            }
+            "undo" => {
+                let undone = history.current_cmd().to_string();
+                match history.undo() {
+                    Some(state) => {
+                        *ranking = state;
+                        println!("{cmd}: `{undone}` undone.");
                     }
+                    None => print_error(format!("{cmd}: Nothing to undo !!!")),
                 }
             }
+            "redo" => match history.redo() {
+                Some(state) => {
+                    *ranking = state;
+                    println!("{cmd}: `{}` redone.", history.current_cmd());
+                }
+                None => print_error(format!("{cmd}: Nothing to redo !!!")),
+            },
+            "history" => {
+                println!(
+                    "{cmd}: (step, command, forbidden in assignments, rankings set)\n{}",
+                    history.lines(|r| format!(
+                        "({}, {} / {})",
+                        match &r.assignments_data {
+                            Some(a) => a.number_of_forbidden_used().to_string(),
+                            None => String::from("-"),
+                        },
+                        r.rankers.iter().filter(|x| x.ranking.is_some()).count(),
+                        r.rankers.len()
+                    ))
+                );
+            }
             "quit" => {
                 break 'edit;
             }
-            _ => println!("Unknown command: {}", cmd),
+            _ => print_error(format!("Unknown command: {}", cmd)),
         }
+        history.record(line, ranking);
     }
 }
 
@@ -744,6 +807,72 @@ pub fn prkdfa(ranking: &Ranking) {
     println!("\n---------------------------------------------------------------------\n");
 }
 
+// `resize_ranking` - `rmranker <ids> [k=K]`, `rmranked <ids> [k=K]`, `addranker [count] [k=K]`,
+// `addranked [count] [k=K]` (see `Ranking::resized`)
+pub fn resize_ranking(ranking: &mut Ranking, cmd: &str, arg: &str) {
+    let Some(assignments) = &ranking.assignments_data else {
+        print_error(format!("{cmd}: NO assignments_data !!!"));
+        return;
+    };
+    let mut ids: Vec<usize> = vec![];
+    let mut k = None;
+    for word in arg.split_whitespace() {
+        let parsed = match word.strip_prefix("k=") {
+            Some(value) => value.parse().map(|x| k = Some(x)),
+            None => word.parse().map(|x| ids.push(x)),
+        };
+        if let Err(err) = parsed {
+            print_error(format!("{cmd}: `{word}`: {err}"));
+            return;
+        }
+    }
+    let count = *ids.first().unwrap_or(&1);
+    let result = match cmd {
+        "rmranker" => assignments.without_lefts(&ids, k),
+        "rmranked" => assignments.without_rights(&ids, k),
+        "addranker" => assignments.with_new_lefts(count, k),
+        _ => assignments.with_new_rights(count, k),
+    }
+    .and_then(|(resized, resize)| {
+        let (new_ranking, kept) = ranking.resized(resized, &resize)?;
+        Ok((new_ranking, kept, resize))
+    });
+    match result {
+        Ok((new_ranking, kept, resize)) => {
+            *ranking = new_ranking;
+            println!("{cmd}: assignments: {resize}.");
+            println!(
+                "{cmd}: rankings kept by rankers {kept:?}, the other rankers have to rank again."
+            );
+        }
+        Err(err) => print_error(format!("{cmd}: {err}")),
+    }
+}
+
+// `prcomp` - connectivity of the comparison graph of ranked (see `comparison::ComparisonGraph`)
+pub fn prcomp(ranking: &Ranking) {
+    let cmd = "prcomp";
+    let Some(assignments) = &ranking.assignments_data else {
+        print_error(format!("{cmd}: NO assignments_data !!!"));
+        return;
+    };
+    let report = assignments.comparison_graph().report();
+    println!("{cmd}: Comparison graph of ranked (ranked sharing a ranker):\n{report}");
+    for (ranked, compared, shared) in &report.weakest {
+        println!(
+            "  ranked {ranked} ({:?}) compared with {compared} ranked by {shared} shared rankers",
+            ranking.ranked[*ranked].info
+        );
+    }
+    let warnings = report.warnings();
+    if warnings.is_empty() {
+        println!("{cmd}: The results are comparable.");
+    }
+    for warning in warnings {
+        println!("#WARNING# {cmd}: {warning}");
+    }
+}
+
 // input one-line ranking JSONs for rankers (left IDs)
 pub fn irlvrvj(ranking: &mut Ranking) {
     let cmd = "irlvrvj";
@@ -758,12 +887,14 @@ pub fn irlvrvj(ranking: &mut Ranking) {
                 let left = &deserialized.left;
                 let right = &deserialized.right;
                 if left.len() != 1 {
-                    println!("{cmd}: left = {left:?} should have one element - the ranker id");
+                    print_error(format!(
+                        "{cmd}: left = {left:?} should have one element - the ranker id"
+                    ));
                 } else {
                     // Ok, do "ir"
                     let ranker = left[0];
                     if let Err(err) = ranking.set_ranking(ranker, right.clone()) {
-                        println!("{cmd}: {err}");
+                        print_error(format!("{cmd}: {err}"));
                     } else {
                         println!("{cmd}: added ranking: {ranker} -> {right:?}:");
                     }
@@ -779,7 +910,7 @@ pub fn irlvrvj(ranking: &mut Ranking) {
 }
 
 // Ranker
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Ranker {
     // id: usize,                 // let id be its position in Ranking.rankers
     info: Option<String>,        // optional info
@@ -793,18 +924,33 @@ impl Ranker {
 }
 
 // Ranked
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Ranked {
     // id: usize,                  // let id be its position in Ranking.ranked
     info: Option<String>, // optional info
 }
 
+// ComparisonCache - pairs of the last comparison graph analysis with its warnings; only a cache,
+// so it does not take part in the comparison of rankings
+#[derive(Debug, Clone, Default)]
+struct ComparisonCache(Option<Compared>);
+
+// Compared - the analysed pairs and the warnings of their comparison graph
+type Compared = (Vec<(usize, usize)>, Vec<String>);
+
+impl PartialEq for ComparisonCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
 // Ranking
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Ranking {
     assignments_data: Option<Assignments>,
-    rankers: Vec<Ranker>, // sequence of m rankers
-    ranked: Vec<Ranked>,  // sequence of n ranked
+    rankers: Vec<Ranker>,        // sequence of m rankers
+    ranked: Vec<Ranked>,         // sequence of n ranked
+    comparison: ComparisonCache, // see `warnings`
 }
 
 impl Ranking {
@@ -813,6 +959,7 @@ impl Ranking {
             assignments_data: None,
             rankers: vec![],
             ranked: vec![],
+            comparison: ComparisonCache::default(),
         }
     }
 
@@ -892,6 +1039,43 @@ impl Ranking {
         Ok(ranking)
     }
 
+    // `resized` - Ranking with `assignments` resized from the current ones by `resize` (see
+    // `Assignments::resized`): the infos follow the remapped ids and the unaffected rankers (assigned
+    // to the same ranked) keep their rankings; returns also the new ids of the rankers keeping rankings
+    pub fn resized(
+        &self,
+        assignments: Assignments,
+        resize: &Resize,
+    ) -> Result<(Self, Vec<usize>), Box<dyn Error>> {
+        let Some(my_assignments) = &self.assignments_data else {
+            return Err("resized: NO assignments_data !!!???".into());
+        };
+        let unaffected = resize.unaffected_lefts(my_assignments, &assignments);
+        let mut ranking = Ranking::new();
+        ranking.assignments_data = Some(assignments);
+        ranking.make_vectors();
+        for (l, l1) in resize.left_map.iter().enumerate() {
+            if let Some(l1) = l1 {
+                ranking.rankers[*l1].info = self.rankers[l].info.clone();
+            }
+        }
+        for (r, r1) in resize.right_map.iter().enumerate() {
+            if let Some(r1) = r1 {
+                ranking.ranked[*r1].info = self.ranked[r].info.clone();
+            }
+        }
+        let mut kept = vec![];
+        for (l, l1) in unaffected {
+            if let Some(old) = &self.rankers[l].ranking {
+                let remapped: Option<Vec<usize>> =
+                    old.iter().map(|r| resize.right_map[*r]).collect();
+                ranking.rankers[l1].ranking = remapped;
+                kept.push(l1);
+            }
+        }
+        Ok((ranking, kept))
+    }
+
     // try to create Ranking with ranked ids reduced to sorted ranked_ids with forbidden remapped to the new ids
     pub fn ranked_reduced_to(
         &self,
@@ -1171,6 +1355,22 @@ impl Ranking {
 
         assignments.test_forbidden()?;
         // self.test_rankings()?;    // to noisy ...
+        // ranked compared poorly (see `prcomp`), analysed again only after the assignments change:
+        let pairs = assignments.get_pairs_of_ids();
+        if self
+            .comparison
+            .0
+            .as_ref()
+            .is_none_or(|(compared, _)| *compared != pairs)
+        {
+            let warnings = assignments.comparison_graph().report().warnings();
+            self.comparison.0 = Some((pairs, warnings));
+        }
+        if let Some((_, warnings)) = &self.comparison.0 {
+            for warning in warnings {
+                writeln!(&mut err, "#WARNING# In `Ranking.warnings`: {warning}")?;
+            }
+        }
         // TODO: more warnings ...
         if err.len() > 0 {
             Err(err.into())
@@ -1382,6 +1582,7 @@ impl From<&SerdeRanking> for Ranking {
             assignments_data,
             rankers: item.rankers.clone(),
             ranked: item.ranked.clone(),
+            comparison: ComparisonCache::default(),
         }
     }
 }
@@ -0,0 +1,867 @@
+// Non-interactive subcommands of the `kmn-pairs` binary
+
+use crate::cmd::*;
+use crate::cover::*;
+use crate::cp::*;
+use crate::exact::*;
+use crate::kmn_serde::*;
+use crate::menu::*;
+use crate::merge::*;
+use crate::progress::*;
+use crate::replay::*;
+use crate::sample::*;
+use crate::skeleton::*;
+use crate::split::*;
+use crate::strategy::*;
+use crate::trace::*;
+use crate::*;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::fs;
+use std::process::Command;
+
+// exit codes
+pub const EXIT_SOLVED: i32 = 0; // no forbidden in the assignments
+pub const EXIT_FORBIDDEN: i32 = 1; // some forbidden remain in the assignments
+pub const EXIT_INVALID: i32 = 2; // invalid input (arguments, files, data)
+pub const EXIT_DIFFERENT: i32 = 1; // `replay`: results differ from the expected ones
+pub const EXIT_UNASSIGNED: i32 = 1; // `split`: some lefts or rights are in no track
+
+pub const CLI_HELP: &str = "
+usage:
+    kmn-pairs                      run the interactive menu
+    kmn-pairs [--script FILE [--keep-going]] [--seed SEED] [--out FILE]
+                                   execute the menu commands from FILE (stop on the first error
+                                   unless --keep-going; lines starting with `#` are comments),
+                                   --seed SEED for reproducible sessions, --out FILE for final JSON
+    kmn-pairs generate --k K --m M --n N [--p P] [--skeleton KIND] [--seed SEED] [--out FILE]
+                                   default assignments for (k, m, n) (or (m, n, p) without --k)
+                                   with the skeleton KIND in {cyclic, block-circulant, random-biregular,
+                                   min-overlap, covering, max-girth} (cyclic by default)
+    kmn-pairs solve --in FILE --strategy S (--max MAX | --time DURATION) [--l-percent L] [--seed SEED] [--out FILE]
+                    [--trace TRACE]
+                                   search with S in {rl, rr, rlr, sl, sr, slr, bsr, bslr, sbrk}
+                                   for MAX steps and/or DURATION (e.g. 30s, 2m; neither for sbrk) with progress reports,
+                                   Ctrl-C stops the search keeping the best assignments found
+    kmn-pairs search --in FILE STRATEGY [NAME=VALUE ...] [--seed SEED] [--out FILE] [--trace TRACE]
+                                   search with STRATEGY of the registry and its parameters,
+                                   max=MAX and/or time=DURATION limit the search
+                                   (`kmn-pairs search` lists the strategies)
+                                   --trace TRACE writes the trace of the search (forbidden over steps,
+                                   new bests, rates of moves) as JSON (TRACE ending with .json) or CSV
+    kmn-pairs exact --in FILE [--count] [--nodes N] [--out FILE]
+                                   exact solver for small instances: assignments with the minimal
+                                   number of forbidden pairs, --count also counts the optimal ones,
+                                   fails after N search nodes (200000 by default)
+    kmn-pairs repair --in FILE [--out FILE]
+                                   minimal-disruption repair: assignments with the fewest forbidden
+                                   pairs changing the fewest pairs of FILE (prints the changes)
+    kmn-pairs merge FIRST SECOND [NAME=VALUE ...] [--out FILE]
+                                   merge the assignments of two files: lefts=MAPPING and rights=MAPPING
+                                   map the ids of SECOND (same, disjoint, labels or ID:MERGED_ID,...),
+                                   k=K, prefer=first|second resolves conflicting pairs; the forbidden
+                                   pairs are united and the merged pairs repaired and tested
+    kmn-pairs split --in FILE TRACKS [k=K] [--out FILE]
+                                   split into tracks solved separately with the fewest forbidden pairs:
+                                   TRACKS is N (clustering of the allowed pairs), L,L,.../R,R,...;...
+                                   (ids or labels of FILE) or a JSON file of tracks; prints the JSON
+                                   report of the tracks with the original ids (exit code 1 also if
+                                   some lefts or rights are in no track)
+    kmn-pairs cp --in FILE [NAME=VALUE ...] [--out FILE]
+                                   constraint programming solver: assignments without forbidden pairs
+                                   satisfying side constraints (max_overlap=N, conflict=L1-L2,
+                                   group=L1,L2,...:MIN:MAX) with minimal max left overlap
+                                   (minimize=overlap|none), limited by nodes=N and time=DURATION
+    kmn-pairs sample --in FILE [--steps STEPS] [--seed SEED] [--out FILE]
+                                   uniformly random valid assignments (of any structure) after STEPS
+                                   steps of a switch Markov chain (100 per pair by default)
+    kmn-pairs cover --in FILE [--steps STEPS] [--seed SEED] [--out FILE]
+                                   assignments where as many pairs of rights as possible share a left
+                                   (and no new forbidden pairs) after STEPS steps of annealing
+                                   (1000 per pair by default)
+    kmn-pairs picture --in FILE [--out PICTURE]
+                                   picture of the assignments with the labels of FILE: Graphviz DOT
+                                   (SVG drawn without Graphviz if PICTURE ends with .svg)
+    kmn-pairs test FILE            test assignments and forbidden
+    kmn-pairs add-forbidden FILE --csv CSV [--out FILE]
+                                   add forbidden pairs `l,r` from lines of CSV
+    kmn-pairs replay SCRIPT (--expected JSON | --solution TXT) [--transcript TXT] [--seed SEED] [--record]
+                                   replay the session SCRIPT (with SEED = 0 by default) and compare
+                                   the final JSON (and the printed transcript) with the expected files,
+                                   --record writes the expected files instead; --solution TXT (printed
+                                   by `p`) must have the forbidden pairs of the session and valid
+                                   assignments avoiding them
+    kmn-pairs help                 print this help
+
+    JSON is printed to FILE of --out (or to stdout), messages are printed to stderr.
+    exit codes: 0 - no forbidden in assignments, 1 - forbidden remain, 2 - invalid input
+    (`replay`: 0 - results as expected, 1 - results differ, 2 - invalid input)
+";
+
+// `CliArgs`: positional arguments, `--name value` options and `--flag` flags
+#[derive(Debug)]
+pub struct CliArgs {
+    pub positional: Vec<String>,
+    pub options: Vec<(String, String)>,
+    pub flags: Vec<String>,
+}
+
+impl CliArgs {
+    // each `--name` not in `flags` takes the next argument as its value
+    pub fn parse(args: &[String], flags: &[&str]) -> Result<Self, Box<dyn Error>> {
+        let mut positional = vec![];
+        let mut options = vec![];
+        let mut found_flags = vec![];
+        let mut i = 0;
+        while i < args.len() {
+            if let Some(name) = args[i].strip_prefix("--") {
+                if flags.contains(&name) {
+                    found_flags.push(name.to_string());
+                    i += 1;
+                    continue;
+                }
+                let Some(value) = args.get(i + 1) else {
+                    return Err(format!("option --{name} has no value !!!").into());
+                };
+                options.push((name.to_string(), value.clone()));
+                i += 2;
+            } else {
+                positional.push(args[i].clone());
+                i += 1;
+            }
+        }
+        Ok(Self {
+            positional,
+            options,
+            flags: found_flags,
+        })
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|x| x == name)
+    }
+
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    // parsed value of the option `name` (`None` if not given)
+    pub fn parsed<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, Box<dyn Error>>
+    where
+        <T as std::str::FromStr>::Err: std::error::Error + 'static,
+    {
+        match self.option(name) {
+            None => Ok(None),
+            Some(value) => match value.parse::<T>() {
+                Ok(v) => Ok(Some(v)),
+                Err(err) => Err(format!("--{name} {value}: {err}").into()),
+            },
+        }
+    }
+
+    pub fn required<T: std::str::FromStr>(&self, name: &str) -> Result<T, Box<dyn Error>>
+    where
+        <T as std::str::FromStr>::Err: std::error::Error + 'static,
+    {
+        self.parsed(name)?
+            .ok_or_else(|| format!("missing option --{name} !!!").into())
+    }
+
+    // fail on options not in `known`
+    pub fn check_options(&self, known: &[&str]) -> Result<(), Box<dyn Error>> {
+        for (name, _) in &self.options {
+            if !known.contains(&name.as_str()) {
+                return Err(format!("unknown option --{name} !!!").into());
+            }
+        }
+        Ok(())
+    }
+}
+
+// `run` the subcommand from `args` (without the program name) and return the exit code
+pub fn run(args: &[String]) -> i32 {
+    let Some((subcommand, rest)) = args.split_first() else {
+        eprintln!("{CLI_HELP}");
+        return EXIT_INVALID;
+    };
+    if subcommand == "replay" {
+        return run_replay(rest);
+    }
+    let cli_args = match CliArgs::parse(rest, &["count"]) {
+        Ok(cli_args) => cli_args,
+        Err(err) => {
+            eprintln!("{subcommand}: {err}");
+            return EXIT_INVALID;
+        }
+    };
+    let result = match subcommand.as_str() {
+        "generate" => generate(&cli_args),
+        "solve" => solve(&cli_args),
+        "search" => search(&cli_args),
+        "exact" => exact(&cli_args),
+        "cp" => cp(&cli_args),
+        "repair" => repair(&cli_args),
+        "merge" => merge(&cli_args),
+        "split" => split(&cli_args),
+        "sample" => sample(&cli_args),
+        "cover" => cover(&cli_args),
+        "picture" => picture(&cli_args),
+        "test" => test(&cli_args),
+        "add-forbidden" => add_forbidden(&cli_args),
+        "help" | "-h" | "--help" => {
+            println!("{CLI_HELP}");
+            return EXIT_SOLVED;
+        }
+        _ => Err(format!("unknown subcommand: {subcommand}\n{CLI_HELP}").into()),
+    };
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("{subcommand}: {err}");
+            EXIT_INVALID
+        }
+    }
+}
+
+// `MenuOptions` - options of the interactive (or scripted) menus
+#[derive(Debug, Default)]
+pub struct MenuOptions {
+    pub script_mode: bool,   // commands are read from `--script FILE`
+    pub out: Option<String>, // `--out FILE` for the final JSON
+}
+
+// `menu_options` sets the input of the menus from `--script FILE` (with `--keep-going`)
+// and the seed of their random number generators from `--seed SEED`
+pub fn menu_options(args: &[String]) -> Result<MenuOptions, Box<dyn Error>> {
+    let cli_args = CliArgs::parse(args, &["keep-going"])?;
+    cli_args.check_options(&["script", "seed", "out"])?;
+    if !cli_args.positional.is_empty() {
+        return Err(format!("unexpected arguments: {:?} !!!", cli_args.positional).into());
+    }
+    set_seed(cli_args.parsed::<u64>("seed")?);
+    let out = cli_args.option("out").map(String::from);
+    let Some(path) = cli_args.option("script") else {
+        if cli_args.flag("keep-going") {
+            return Err("--keep-going without --script !!!".into());
+        }
+        return Ok(MenuOptions {
+            script_mode: false,
+            out,
+        });
+    };
+    let contents = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+    set_script(&contents, cli_args.flag("keep-going"));
+    Ok(MenuOptions {
+        script_mode: true,
+        out,
+    })
+}
+
+// `run_replay` runs `replay` subcommand with `args` (after `replay`) and returns the exit code
+pub fn run_replay(args: &[String]) -> i32 {
+    let result = CliArgs::parse(args, &["record"]).and_then(|cli_args| replay(&cli_args));
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("replay: {err}");
+            EXIT_INVALID
+        }
+    }
+}
+
+// `replay` executes the script by this program in script mode with a fixed seed
+// and compares the final JSON (and the transcript) with the expected files
+pub fn replay(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&["expected", "solution", "transcript", "seed"])?;
+    let [script] = cli_args.positional.as_slice() else {
+        return Err("expected one SCRIPT argument !!!".into());
+    };
+    let solution = cli_args.option("solution");
+    let expected = match (cli_args.option("expected"), solution) {
+        (Some(expected), None) => expected,
+        (None, Some(_)) if !cli_args.flag("record") => "",
+        _ => return Err("expected one of the options --expected and --solution !!!".into()),
+    };
+    let seed = cli_args.parsed::<u64>("seed")?.unwrap_or(0);
+    let out = std::env::temp_dir().join(format!("kmn-pairs-replay-{}.json", std::process::id()));
+    let output = Command::new(std::env::current_exe()?)
+        .args(["--script", script, "--seed", &seed.to_string(), "--out"])
+        .arg(&out)
+        .output()?;
+    let transcript = String::from_utf8_lossy(&output.stdout).to_string();
+    // (exit code 1 of `kmn-pairs` means only that some forbidden remain)
+    let actual = fs::read_to_string(&out).map_err(|err| format!("no JSON from {script}: {err}"));
+    let _ = fs::remove_file(&out);
+    // a failed script is not compared (nor recorded)
+    if !matches!(
+        output.status.code(),
+        Some(EXIT_SOLVED) | Some(EXIT_FORBIDDEN)
+    ) {
+        eprint!("{}", String::from_utf8_lossy(&output.stderr));
+        return Err(format!("{script} finished with {} !!!", output.status).into());
+    }
+    let actual = actual?;
+
+    if cli_args.flag("record") {
+        fs::write(expected, &actual)?;
+        eprintln!("replay: recorded {expected}");
+        if let Some(path) = cli_args.option("transcript") {
+            fs::write(path, &transcript)?;
+            eprintln!("replay: recorded {path}");
+        }
+        return Ok(EXIT_SOLVED);
+    }
+
+    let mut result = Replay::default();
+    if let Some(path) = solution {
+        let printed =
+            Printed::parse(&fs::read_to_string(path)?).map_err(|err| format!("{path}: {err}"))?;
+        result.json_diffs = solution_diffs(&printed, &serde_json::from_str(&actual)?);
+        println!("{result}");
+        return Ok(if result.passed() {
+            EXIT_SOLVED
+        } else {
+            EXIT_DIFFERENT
+        });
+    }
+    let expected_value: serde_json::Value = serde_json::from_str(&fs::read_to_string(expected)?)
+        .map_err(|err| format!("{expected}: {err}"))?;
+    let actual_value: serde_json::Value = serde_json::from_str(&actual)?;
+    json_diff("$", &expected_value, &actual_value, &mut result.json_diffs);
+    if let Some(path) = cli_args.option("transcript") {
+        result.transcript_diff = transcript_diff(&fs::read_to_string(path)?, &transcript);
+    }
+    println!("{result}");
+    Ok(if result.passed() {
+        EXIT_SOLVED
+    } else {
+        EXIT_DIFFERENT
+    })
+}
+
+// `solution_diffs` of the `printed` solution from the replayed session `actual`: (k, m, n) and the forbidden
+// pairs differing, the printed assignments invalid or using the forbidden pairs of the session
+fn solution_diffs(printed: &Printed, actual: &SerdeKmnAssignment) -> Vec<JsonDiff> {
+    let mut out = vec![];
+    let session = Assignments::from(actual);
+    if session.get_kmn() != printed.kmn {
+        out.push(JsonDiff {
+            path: String::from("$.(k,m,n)"),
+            expected: Some(serde_json::json!(printed.kmn)),
+            actual: Some(serde_json::json!(session.get_kmn())),
+        });
+        return out;
+    }
+    json_diff(
+        "$.forbidden",
+        &serde_json::json!(printed.forbidden),
+        &serde_json::json!(session.forbidden()),
+        &mut out,
+    );
+    let (_, m, n) = printed.kmn;
+    let outside: Vec<&(usize, usize)> = printed
+        .pairs
+        .iter()
+        .filter(|(l, r)| *l >= m || *r >= n)
+        .collect();
+    if !outside.is_empty() {
+        out.push(JsonDiff {
+            path: String::from("$.solution (pairs outside m x n)"),
+            expected: Some(serde_json::json!([])),
+            actual: Some(serde_json::json!(outside)),
+        });
+    } else {
+        let mut solution = session.clone();
+        solution.set_pairs_of_ids(&printed.pairs);
+        if let Err(err) = solution.test_assignments() {
+            out.push(JsonDiff {
+                path: String::from("$.solution (valid assignments)"),
+                expected: None,
+                actual: Some(serde_json::json!(err.to_string())),
+            });
+        }
+    }
+    let used: Vec<&(usize, usize)> = printed
+        .pairs
+        .iter()
+        .filter(|x| session.forbidden().contains(x))
+        .collect();
+    if !used.is_empty() {
+        out.push(JsonDiff {
+            path: String::from("$.solution (forbidden pairs used)"),
+            expected: Some(serde_json::json!([])),
+            actual: Some(serde_json::json!(used)),
+        });
+    }
+    out
+}
+
+// exit code after the menus executed the script (`EXIT_INVALID` if some errors were reported)
+pub fn script_exit_code() -> i32 {
+    match script_errors() {
+        Some(errors) if errors > 0 => {
+            eprintln!("Script finished with {errors} error(s) !!!");
+            EXIT_INVALID
+        }
+        _ => EXIT_SOLVED,
+    }
+}
+
+// exit code for the current state of `assignments`
+pub fn exit_code(assignments: &Assignments) -> i32 {
+    if assignments.number_of_forbidden_used() == 0 {
+        EXIT_SOLVED
+    } else {
+        EXIT_FORBIDDEN
+    }
+}
+
+// load and test assignments from `path`
+pub fn load_tested(path: &str) -> Result<Assignments, Box<dyn Error>> {
+    let mut assignments = read_assignments_file(path).map_err(|err| format!("{path}: {err}"))?;
+    assignments
+        .test_assignments()
+        .map_err(|err| format!("{path}:\n{err}"))?;
+    if let Err(err) = assignments.test_forbidden() {
+        eprintln!("{path}:\n{err}");
+    }
+    Ok(assignments)
+}
+
+// print JSON of `assignments` to `--out` file or to stdout
+pub fn output(assignments: &Assignments, cli_args: &CliArgs) -> Result<(), Box<dyn Error>> {
+    output_serde(&SerdeKmnAssignment::from(assignments), cli_args)
+}
+
+// print JSON assignments data (e.g. with labels) to `--out` file or to stdout
+pub fn output_serde(data: &SerdeKmnAssignment, cli_args: &CliArgs) -> Result<(), Box<dyn Error>> {
+    match cli_args.option("out") {
+        Some(path) => {
+            write_serde_file(data, path)?;
+            eprintln!("written: {path}");
+        }
+        None => {
+            println!("{}", serde_json::to_string(data)?);
+        }
+    }
+    Ok(())
+}
+
+// random number generator seeded by `--seed` (if given)
+pub fn cli_rng(cli_args: &CliArgs) -> Result<StdRng, Box<dyn Error>> {
+    Ok(match cli_args.parsed::<u64>("seed")? {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    })
+}
+
+pub fn generate(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&["k", "m", "n", "p", "skeleton", "seed", "out"])?;
+    let (m, n) = (
+        cli_args.required::<usize>("m")?,
+        cli_args.required::<usize>("n")?,
+    );
+    let (k, p) = (
+        cli_args.parsed::<usize>("k")?,
+        cli_args.parsed::<usize>("p")?,
+    );
+    if k.is_some() && p.is_some() {
+        return Err("use either --k or --p !!!".into());
+    }
+    // the same conditions as in `Assignments::new_kmnp`
+    Pairs::kmnp_pairs(k, m, n, p)?;
+    let mut assignments = Assignments::new_kmnp(k, m, n, p);
+    let skeleton = cli_args
+        .option("skeleton")
+        .map(str::parse::<SkeletonKind>)
+        .transpose()?
+        .unwrap_or_default();
+    if !skeleton.is_cyclic() {
+        let (k, m, n) = assignments.get_kmn();
+        assignments = Assignments::with_skeleton(skeleton, k, m, n, &mut cli_rng(cli_args)?);
+        if assignments.skeleton() != skeleton {
+            eprintln!("#WARNING# No {skeleton} skeleton found, the cyclic one used.");
+        }
+    }
+    eprintln!(
+        "Default assignments for (k, m, n, p)=({:?},{m},{n},{p:?}) set!",
+        k
+    );
+    output(&assignments, cli_args)?;
+    Ok(exit_code(&assignments))
+}
+
+// `cli_progress` prints progress of the search `cmd` to stderr (and traces it with --trace FILE)
+fn cli_progress(cmd: &str, cli_args: &CliArgs) -> Traced<PrintProgress> {
+    Traced {
+        inner: PrintProgress {
+            cmd: cmd.to_string(),
+            to_stderr: true,
+        },
+        trace: cli_args.option("trace").map(|_| Trace::new(cmd)),
+    }
+}
+
+// `write_trace` writes `trace` to the FILE of --trace and prints its summary and chart to stderr
+fn write_trace(trace: Option<Trace>, cli_args: &CliArgs) -> Result<(), Box<dyn Error>> {
+    if let (Some(trace), Some(path)) = (trace, cli_args.option("trace")) {
+        trace.write_file(path)?;
+        eprint!("{}", trace.summary());
+        eprint!("{}", trace.chart(60, 10));
+    }
+    Ok(())
+}
+
+// search with the menu `strategy` on `assignments`
+pub fn search_with(
+    assignments: &mut Assignments,
+    strategy: &str,
+    budget: Budget,
+    l_percent: usize,
+    rng: &mut impl Rng,
+    progress: &mut dyn SearchProgress,
+) -> Result<(Steps, Steps, Forbidden), Box<dyn Error>> {
+    let percent = Side::LeftPercent(l_percent);
+    Ok(match strategy {
+        "rl" => Permute::search_with_progress(assignments, Side::Left, budget, rng, progress)?,
+        "rr" => Permute::search_with_progress(assignments, Side::Right, budget, rng, progress)?,
+        "rlr" => Permute::search_with_progress(assignments, percent, budget, rng, progress)?,
+        "sl" => Swap::search_with_progress(assignments, Side::Left, budget, rng, progress)?,
+        "sr" => Swap::search_with_progress(assignments, Side::Right, budget, rng, progress)?,
+        "slr" => Swap::search_with_progress(assignments, percent, budget, rng, progress)?,
+        "bsr" => BackSwap::search_with_progress(assignments, Side::Right, budget, rng, progress)?,
+        "bslr" => BackSwap::search_with_progress(assignments, percent, budget, rng, progress)?,
+        "sbrk" => {
+            let pairs = assignments.try_switching_endpoints(assignments.get_pairs_of_ids())?;
+            assignments.set_pairs_of_ids(&pairs);
+            assignments.test_assignments()?;
+            (
+                Steps(0),
+                Steps(0),
+                Forbidden(assignments.number_of_forbidden_used()),
+            )
+        }
+        _ => {
+            return Err(format!("unknown strategy: {strategy} !!!").into());
+        }
+    })
+}
+
+pub fn solve(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&[
+        "in",
+        "strategy",
+        "max",
+        "time",
+        "l-percent",
+        "seed",
+        "out",
+        "trace",
+    ])?;
+    let path = cli_args
+        .option("in")
+        .ok_or("missing option --in !!!")?
+        .to_string();
+    let strategy = cli_args.option("strategy").unwrap_or("bslr").to_string();
+    let time = cli_args.option("time").map(parse_duration).transpose()?;
+    // with --time the search may run until the time budget is exhausted, `sbrk` does not step
+    let max = match time.is_some() || strategy == "sbrk" {
+        true => cli_args.parsed::<usize>("max")?.unwrap_or(usize::MAX),
+        false => cli_args.required::<usize>("max")?,
+    };
+    let l_percent = cli_args.parsed::<usize>("l-percent")?.unwrap_or(50);
+    if l_percent > 100 {
+        return Err(format!("--l-percent {l_percent} > 100 !!!").into());
+    }
+    let mut rng = cli_rng(cli_args)?;
+    let mut assignments = load_tested(&path)?;
+    let mut progress = cli_progress(&strategy, cli_args);
+    let (Steps(l_steps), Steps(r_steps), Forbidden(f)) = search_with(
+        &mut assignments,
+        &strategy,
+        Budget { max, time },
+        l_percent,
+        &mut rng,
+        &mut progress,
+    )?;
+    write_trace(progress.trace, cli_args)?;
+    // the search may end in a state worse than its backup
+    if assignments.f_min_backup_update(assignments.get_pairs_of_ids())
+        < assignments.number_of_forbidden_used()
+    {
+        assignments.f_min_backup_restore();
+    }
+    eprintln!(
+        "{strategy}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
+        l_steps, r_steps, f
+    );
+    output(&assignments, cli_args)?;
+    Ok(exit_code(&assignments))
+}
+
+// `search` - `kmn-pairs search --in FILE STRATEGY name=value ...` with a strategy from the registry
+pub fn search(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&["in", "seed", "out", "trace"])?;
+    let Some((name, args)) = cli_args.positional.split_first() else {
+        println!(
+            "strategies [default parameters]:\n{}",
+            with_registry(|registry| registry.help())
+        );
+        return Ok(EXIT_SOLVED);
+    };
+    let path = cli_args.option("in").ok_or("missing option --in !!!")?;
+    let args: Vec<&str> = args.iter().map(|x| x.as_str()).collect();
+    let args = split_name_values(&args)?;
+    let mut rng = cli_rng(cli_args)?;
+    let mut assignments = load_tested(path)?;
+    let mut progress = cli_progress(name, cli_args);
+    let outcome = search_by_name(name, &args, &mut assignments, &mut rng, &mut progress)?;
+    write_trace(progress.trace, cli_args)?;
+    // the search may end in a state worse than its backup
+    if assignments.f_min_backup_update(assignments.get_pairs_of_ids())
+        < assignments.number_of_forbidden_used()
+    {
+        assignments.f_min_backup_restore();
+    }
+    eprintln!(
+        "{name}: After {} steps ({}), {}-forbidden-assignment backuped.",
+        outcome.steps, outcome.stop, outcome.forbidden
+    );
+    eprint!("{}", outcome.report);
+    output(&assignments, cli_args)?;
+    Ok(exit_code(&assignments))
+}
+
+// `exact` - `kmn-pairs exact --in FILE` assignments with the minimal number of forbidden pairs
+pub fn exact(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&["in", "nodes", "out"])?;
+    let path = cli_args.option("in").ok_or("missing option --in !!!")?;
+    let solver = ExactSolver {
+        count: cli_args.flag("count"),
+        node_limit: cli_args
+            .parsed::<usize>("nodes")?
+            .unwrap_or(EXACT_NODE_LIMIT),
+    };
+    let mut assignments = load_tested(path)?;
+    let outcome = solver.solve(&assignments)?;
+    assignments.set_pairs_of_ids(&outcome.pairs);
+    eprintln!("exact: {outcome}.");
+    output(&assignments, cli_args)?;
+    Ok(exit_code(&assignments))
+}
+
+// `repair` - `kmn-pairs repair --in FILE` valid assignments changing as few pairs as possible
+pub fn repair(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&["in", "out"])?;
+    let path = cli_args.option("in").ok_or("missing option --in !!!")?;
+    let mut assignments = load_tested(path)?;
+    let (pairs, diff) = assignments.repaired()?;
+    assignments.set_pairs_of_ids(&pairs);
+    eprintln!("repair: {}", diff.header());
+    eprintln!("repair: lefts affected: {:?}", diff.lefts());
+    eprint!("{}", diff.body(false));
+    output(&assignments, cli_args)?;
+    Ok(exit_code(&assignments))
+}
+
+// `merge` - `kmn-pairs merge FIRST SECOND name=value ...` merged assignments of two files
+pub fn merge(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&["out"])?;
+    let [first, second, args @ ..] = cli_args.positional.as_slice() else {
+        return Err("expected FIRST and SECOND file arguments !!!".into());
+    };
+    let args: Vec<&str> = args.iter().map(|x| x.as_str()).collect();
+    let mut options = MergeOptions::default();
+    for (name, value) in split_name_values(&args)? {
+        options.set_parameter(&name, &value)?;
+    }
+    let (data1, data2) = (read_serde_file(first)?, read_serde_file(second)?);
+    let merged = merge_data(&data1, &data2, &options)?;
+    eprintln!("merge: {merged}");
+    if !merged.conflicts.is_empty() {
+        eprint!(
+            "merge: conflicts (l r assigned only by):\n{}",
+            merged.conflicts_body()
+        );
+    }
+    output_serde(&merged.serde(), cli_args)?;
+    Ok(exit_code(&merged.assignments))
+}
+
+// `split` - `kmn-pairs split --in FILE TRACKS k=K` the tracks solved separately (JSON report)
+pub fn split(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&["in", "out"])?;
+    let path = cli_args.option("in").ok_or("missing option --in !!!")?;
+    let Some((spec, args)) = cli_args.positional.split_first() else {
+        return Err("expected TRACKS argument !!!".into());
+    };
+    let args: Vec<&str> = args.iter().map(|x| x.as_str()).collect();
+    let mut k = None;
+    for (name, value) in split_name_values(&args)? {
+        match name.as_str() {
+            "k" => k = Some(value.parse()?),
+            _ => return Err(format!("unknown parameter `{name}` (parameters: k) !!!").into()),
+        }
+    }
+    let assignments = load_tested(path)?;
+    let split = assignments.split(&Partition::parse(spec)?, assignments.labels(), k)?;
+    eprintln!("split: {}", split.to_string().replace('\n', "\nsplit: "));
+    let json = split.to_json()?;
+    match cli_args.option("out") {
+        Some(out) => {
+            fs::write(out, json + "\n")?;
+            eprintln!("written: {out}");
+        }
+        None => println!("{json}"),
+    }
+    if !split.unassigned_lefts.is_empty() || !split.unassigned_rights.is_empty() {
+        return Ok(EXIT_UNASSIGNED);
+    }
+    Ok(match split.forbidden() {
+        0 => EXIT_SOLVED,
+        _ => EXIT_FORBIDDEN,
+    })
+}
+
+// `picture` - `kmn-pairs picture --in FILE --out PICTURE` DOT or SVG of the assignments
+pub fn picture(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&["in", "out"])?;
+    let path = cli_args.option("in").ok_or("missing option --in !!!")?;
+    let assignments = load_tested(path)?;
+    match cli_args.option("out") {
+        Some(out) => {
+            assignments.write_picture(assignments.labels(), out)?;
+            eprintln!("written: {out}");
+        }
+        None => print!("{}", assignments.to_dot(assignments.labels())),
+    }
+    Ok(exit_code(&assignments))
+}
+
+// `cp` - `kmn-pairs cp --in FILE name=value ...` the constraint programming solver
+pub fn cp(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&["in", "out"])?;
+    let path = cli_args.option("in").ok_or("missing option --in !!!")?;
+    let args: Vec<&str> = cli_args.positional.iter().map(|x| x.as_str()).collect();
+    let mut solver = CpSolver::default();
+    for (name, value) in split_name_values(&args)? {
+        solver.set_parameter(&name, &value)?;
+    }
+    let mut assignments = load_tested(path)?;
+    let outcome = solver.solve(&assignments)?;
+    eprintln!("cp: {outcome}.");
+    if let Some(pairs) = &outcome.pairs {
+        assignments.set_pairs_of_ids(pairs);
+    }
+    output(&assignments, cli_args)?;
+    Ok(exit_code(&assignments))
+}
+
+// `sample` - `kmn-pairs sample --in FILE` uniformly random valid assignments
+pub fn sample(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&["in", "steps", "seed", "out"])?;
+    let path = cli_args.option("in").ok_or("missing option --in !!!")?;
+    let mut rng = cli_rng(cli_args)?;
+    let mut assignments = load_tested(path)?;
+    let steps = cli_args
+        .parsed::<usize>("steps")?
+        .unwrap_or(MIXING_PER_PAIR * assignments.get_pairs_of_ids().len());
+    let (pairs, stats) = sample_uniform(&assignments, steps, &mut rng)?;
+    assignments.set_pairs_of_ids(&pairs);
+    eprintln!("sample: {stats}.");
+    output(&assignments, cli_args)?;
+    Ok(exit_code(&assignments))
+}
+
+// `cover` - `kmn-pairs cover --in FILE` assignments covering as many pairs of rights as possible
+pub fn cover(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&["in", "steps", "seed", "out"])?;
+    let path = cli_args.option("in").ok_or("missing option --in !!!")?;
+    let mut rng = cli_rng(cli_args)?;
+    let mut assignments = load_tested(path)?;
+    let steps = cli_args
+        .parsed::<usize>("steps")?
+        .unwrap_or(COVER_STEPS_PER_PAIR * assignments.get_pairs_of_ids().len());
+    let (pairs, stats) = improve_coverage(&assignments, steps, &mut rng)?;
+    assignments.set_pairs_of_ids(&pairs);
+    eprintln!("cover: {stats}.");
+    output(&assignments, cli_args)?;
+    Ok(exit_code(&assignments))
+}
+
+pub fn test(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&[])?;
+    let [path] = cli_args.positional.as_slice() else {
+        return Err("expected one FILE argument !!!".into());
+    };
+    let assignments = load_tested(path)?;
+    eprintln!("Implemented tests passed.");
+    eprintln!("{}", assignments.forbidden_header());
+    Ok(exit_code(&assignments))
+}
+
+// parse lines `l,r` of CSV (skips empty lines, `#` comments and a non-numeric header)
+pub fn parse_csv_pairs(input: &str) -> Result<Vec<(usize, usize)>, Box<dyn Error>> {
+    let mut out = vec![];
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(|x| x.trim()).collect();
+        let parsed = parse_args::<usize>(&fields);
+        match parsed {
+            Ok(ids) if ids.len() == 2 => out.push((ids[0], ids[1])),
+            Err(_) if out.is_empty() && i == 0 => {} // header
+            _ => {
+                return Err(format!("line {}: `{line}` is not `l,r` !!!", i + 1).into());
+            }
+        }
+    }
+    Ok(out)
+}
+
+pub fn add_forbidden(cli_args: &CliArgs) -> Result<i32, Box<dyn Error>> {
+    cli_args.check_options(&["csv", "out"])?;
+    let [path] = cli_args.positional.as_slice() else {
+        return Err("expected one FILE argument !!!".into());
+    };
+    let csv = cli_args.option("csv").ok_or("missing option --csv !!!")?;
+    let pairs =
+        parse_csv_pairs(&fs::read_to_string(csv)?).map_err(|err| format!("{csv}: {err}"))?;
+    let mut assignments = load_tested(path)?;
+    let (_k, m, n) = assignments.get_kmn();
+    let mut count = 0;
+    for (l, r) in pairs {
+        if !(l < m && r < n) {
+            return Err(format!(
+                "{csv}: (l, r)=({l}, {r}) does not meet the condition: 0 <= l < {m} and 0 <= r < {n} !!!"
+            )
+            .into());
+        }
+        if let Err(err) = assignments.add_forbidden(l, r) {
+            eprint!("{err}");
+        } else {
+            count += 1;
+        }
+    }
+    eprintln!("added {count} forbidden.");
+    if count > 0 {
+        // the backup and the pool were evaluated against the old forbidden pairs
+        let changed = assignments.rescore();
+        eprintln!(
+            "rescore: Forbidden changed: {}, {} of {} pool entries changed the number of forbidden.",
+            assignments.backup_header(),
+            changed,
+            assignments.pool().entries().len()
+        );
+    }
+    eprintln!("{}", assignments.forbidden_header());
+    output(&assignments, cli_args)?;
+    Ok(exit_code(&assignments))
+}
@@ -1,6 +1,7 @@
 use crate::Assignments;
 use crate::Side;
-use crate::intersection_size;
+use crate::progress::*;
+use crate::strategy::*;
 use rand::Rng;
 use std::error::Error;
 use std::fmt::Write;
@@ -61,7 +62,11 @@ pub struct Steps(pub usize);
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Forbidden(pub usize);
 
-pub trait SideSearch {
+pub trait SideSearch: Sized {
+    // name and description of `SideStrategy` of `Self`
+    const NAME: &'static str;
+    const DESCRIPTION: &'static str;
+
     fn action_left(assignments: &mut Assignments, rng: &mut impl Rng);
 
     fn action_right(assignments: &mut Assignments, rng: &mut impl Rng);
@@ -71,64 +76,31 @@ pub trait SideSearch {
         side: Side,
         max: usize,
         rng: &mut impl Rng,
-    ) -> (Steps, Steps, Forbidden) {
-        /*
-        let current_pairs = assignments.get_pairs_of_ids(); // before we start
-        let mut f_min = intersection_size(&current_pairs, &assignments.forbidden);
-        // compare and update assignments.f_min_backup to the actual f_min_backup
-        match &assignments.f_min_backup {
-            None => {
-                assignments.f_min_backup = Some(current_pairs);
-            }
-            Some(pairs) => {
-                let f = intersection_size(&pairs, &assignments.forbidden);
-                if f < f_min {
-                    f_min = f; // update f_min
-                } else {
-                    assignments.f_min_backup = Some(current_pairs); // update f_min_backup
-                }
-            }
-        };
-        */
-
-        // have backup before we start
-        let mut f_min = assignments.f_min_backup_update(assignments.get_pairs_of_ids());
-        let mut l_steps = 0;
-        let mut r_steps = 0;
-        for _step in 1..=max {
-            match side {
-                Side::Left => {
-                    Self::action_left(assignments, rng);
-                    l_steps += 1;
-                }
-                Side::Right => {
-                    Self::action_right(assignments, rng);
-                    r_steps += 1;
-                }
-                Side::LeftPercent(l_p) => {
-                    if rng.random_range(0..100) < l_p {
-                        Self::action_left(assignments, rng);
-                        l_steps += 1;
-                    } else {
-                        Self::action_right(assignments, rng);
-                        r_steps += 1;
-                    }
-                }
-            }
-            let current_pairs = assignments.get_pairs_of_ids();
-            let f = intersection_size(&current_pairs, &assignments.forbidden);
-            // here: `f_min` is actual for current backup
-            if f < f_min {
-                f_min = f;
-                assignments.f_min_backup = Some(current_pairs); // fearless overwrite ! ;-)
-            }
-            if f == 0 {
-                // We have zero forbidden !!!
-                return (Steps(l_steps), Steps(r_steps), Forbidden(f));
-            }
-        }
-        // Zero forbidden not encountered
-        (Steps(l_steps), Steps(r_steps), Forbidden(f_min))
+    ) -> Result<(Steps, Steps, Forbidden), Box<dyn Error>> {
+        Self::search_with_progress(
+            assignments,
+            side,
+            Budget::steps(max),
+            rng,
+            &mut NoProgress(),
+        )
+    }
+
+    // `search_with_progress` - `run_search` of `SideStrategy` of `Self` for `side`
+    fn search_with_progress(
+        assignments: &mut Assignments,
+        side: Side,
+        budget: Budget,
+        rng: &mut impl Rng,
+        progress: &mut dyn SearchProgress,
+    ) -> Result<(Steps, Steps, Forbidden), Box<dyn Error>> {
+        let mut strategy = SideStrategy::<Self>::new(side);
+        let outcome = run_search(&mut strategy, assignments, budget, rng, progress)?;
+        Ok((
+            Steps(strategy.l_steps),
+            Steps(strategy.r_steps),
+            Forbidden(outcome.forbidden),
+        ))
     }
 }
 
@@ -136,6 +108,9 @@ pub trait SideSearch {
 pub struct Permute();
 
 impl SideSearch for Permute {
+    const NAME: &'static str = "permute";
+    const DESCRIPTION: &'static str = "random permutations of left/right IDs (`rl`, `rr`, `rlr`)";
+
     fn action_left(assignments: &mut Assignments, rng: &mut impl Rng) {
         assignments.randomize_left(rng);
     }
@@ -149,6 +124,10 @@ impl SideSearch for Permute {
 pub struct Swap();
 
 impl SideSearch for Swap {
+    const NAME: &'static str = "swap";
+    const DESCRIPTION: &'static str =
+        "swaps of IDs of forbidden with random other IDs (`sl`, `sr`, `slr`)";
+
     fn action_left(assignments: &mut Assignments, rng: &mut impl Rng) {
         assignments.random_swaps_of_l_forbidden(rng);
     }
@@ -163,6 +142,10 @@ impl SideSearch for Swap {
 pub struct BackSwap();
 
 impl SideSearch for BackSwap {
+    const NAME: &'static str = "backswap";
+    const DESCRIPTION: &'static str =
+        "swaps of IDs of forbidden starting from the backup (`bsr`, `bslr`)";
+
     fn action_left(assignments: &mut Assignments, rng: &mut impl Rng) {
         // restore backup
         assignments.f_min_backup_restore();
@@ -185,7 +168,7 @@ impl Assignments {
         side: Side,
         max: usize,
         rng: &mut impl Rng,
-    ) -> (Steps, Steps, Forbidden) {
+    ) -> Result<(Steps, Steps, Forbidden), Box<dyn Error>> {
         Permute::search(self, side, max, rng)
     }
 
@@ -194,7 +177,7 @@ impl Assignments {
         side: Side,
         max: usize,
         rng: &mut impl Rng,
-    ) -> (Steps, Steps, Forbidden) {
+    ) -> Result<(Steps, Steps, Forbidden), Box<dyn Error>> {
         Swap::search(self, side, max, rng)
     }
 
@@ -203,7 +186,7 @@ impl Assignments {
         side: Side,
         max: usize,
         rng: &mut impl Rng,
-    ) -> (Steps, Steps, Forbidden) {
+    ) -> Result<(Steps, Steps, Forbidden), Box<dyn Error>> {
         BackSwap::search(self, side, max, rng)
     }
 }
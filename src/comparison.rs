@@ -0,0 +1,262 @@
+// Comparison graph of rights: two rights are compared when they share a left (for rankings: the
+// aggregated scores of two ranked are comparable when some rankers rank both)
+
+use crate::*;
+use std::collections::VecDeque;
+
+// below this algebraic connectivity (divided by the average weighted degree) the rights are poorly comparable
+pub const WEAK_CONNECTIVITY: f64 = 0.1;
+
+// number of the weakest rights reported
+pub const WEAKEST_REPORTED: usize = 5;
+
+// max number of sweeps of the Jacobi eigenvalue algorithm
+const JACOBI_SWEEPS: usize = 100;
+
+// ComparisonGraph - weights[r1][r2] = number of lefts shared by r1 and r2
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonGraph {
+    pub weights: Vec<Vec<usize>>,
+}
+
+// ComparisonReport - connectivity of the comparison graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonReport {
+    pub components: Vec<Vec<usize>>, // sorted rights of each component (the largest first)
+    pub diameter: Option<usize>,     // max distance of two rights (None if disconnected)
+    pub algebraic_connectivity: f64, // second smallest eigenvalue of the weighted Laplacian
+    pub average_degree: f64,         // average weighted degree (lefts shared with the other rights)
+    pub weakest: Vec<(usize, usize, usize)>, // (right, compared rights, weighted degree) from the weakest
+}
+
+impl ComparisonGraph {
+    // `of` `pairs` with rights in 0..n
+    pub fn of(pairs: &[(usize, usize)], n: usize) -> Self {
+        let m = pairs.iter().map(|(l, _)| l + 1).max().unwrap_or(0);
+        let mut rights_of_left: Vec<Vec<usize>> = vec![vec![]; m];
+        for &(l, r) in pairs {
+            rights_of_left[l].push(r);
+        }
+        let mut weights = vec![vec![0; n]; n];
+        for rights in &rights_of_left {
+            for &r1 in rights {
+                for &r2 in rights {
+                    if r1 != r2 {
+                        weights[r1][r2] += 1;
+                    }
+                }
+            }
+        }
+        Self { weights }
+    }
+
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    // rights compared with `r`
+    pub fn neighbors(&self, r: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).filter(move |x| self.weights[r][*x] > 0)
+    }
+
+    // `distances` from `r` by breadth-first search (None for rights not reachable)
+    pub fn distances(&self, r: usize) -> Vec<Option<usize>> {
+        let mut out = vec![None; self.len()];
+        out[r] = Some(0);
+        let mut queue = VecDeque::from([r]);
+        while let Some(x) = queue.pop_front() {
+            let d = out[x].unwrap_or(0);
+            for y in self.neighbors(x) {
+                if out[y].is_none() {
+                    out[y] = Some(d + 1);
+                    queue.push_back(y);
+                }
+            }
+        }
+        out
+    }
+
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.len()];
+        let mut out = vec![];
+        for r in 0..self.len() {
+            if seen[r] {
+                continue;
+            }
+            let component: Vec<usize> = self
+                .distances(r)
+                .iter()
+                .enumerate()
+                .filter_map(|(x, d)| d.map(|_| x))
+                .collect();
+            for &x in &component {
+                seen[x] = true;
+            }
+            out.push(component);
+        }
+        out.sort_by_key(|c| std::cmp::Reverse(c.len()));
+        out
+    }
+
+    // `diameter` - None if the graph is disconnected
+    pub fn diameter(&self) -> Option<usize> {
+        let mut max = 0;
+        for r in 0..self.len() {
+            for d in self.distances(r) {
+                max = max.max(d?);
+            }
+        }
+        Some(max)
+    }
+
+    // `laplacian` - weighted degrees on the diagonal, minus the weights elsewhere
+    pub fn laplacian(&self) -> Vec<Vec<f64>> {
+        let mut out = vec![vec![0.0; self.len()]; self.len()];
+        for (r1, row) in self.weights.iter().enumerate() {
+            for (r2, w) in row.iter().enumerate() {
+                out[r1][r2] -= *w as f64;
+                out[r1][r1] += *w as f64;
+            }
+        }
+        out
+    }
+
+    // `algebraic_connectivity` - the second smallest eigenvalue of the Laplacian (0 if disconnected)
+    pub fn algebraic_connectivity(&self) -> f64 {
+        if self.len() < 2 {
+            return 0.0;
+        }
+        let eigenvalues = symmetric_eigenvalues(self.laplacian());
+        eigenvalues[1].max(0.0)
+    }
+
+    pub fn report(&self) -> ComparisonReport {
+        let degree = |r: usize| self.weights[r].iter().sum::<usize>();
+        let mut weakest: Vec<(usize, usize, usize)> = (0..self.len())
+            .map(|r| (r, self.neighbors(r).count(), degree(r)))
+            .collect();
+        weakest.sort_by_key(|&(r, compared, degree)| (compared, degree, r));
+        weakest.truncate(WEAKEST_REPORTED);
+        let total: usize = (0..self.len()).map(degree).sum();
+        ComparisonReport {
+            components: self.components(),
+            diameter: self.diameter(),
+            algebraic_connectivity: self.algebraic_connectivity(),
+            average_degree: total as f64 / self.len().max(1) as f64,
+            weakest,
+        }
+    }
+}
+
+impl ComparisonReport {
+    // algebraic connectivity relative to the average weighted degree (about 1 if all the rights are compared
+    // evenly, near 0 if some few comparisons hold the graph together)
+    pub fn relative_connectivity(&self) -> f64 {
+        if self.average_degree == 0.0 {
+            return 0.0;
+        }
+        self.algebraic_connectivity / self.average_degree
+    }
+
+    // `warnings` about poorly comparable rights (empty if the graph is connected well enough)
+    pub fn warnings(&self) -> Vec<String> {
+        let mut out = vec![];
+        let n: usize = self.components.iter().map(|c| c.len()).sum();
+        if n < 2 {
+            return out;
+        }
+        if self.components.len() > 1 {
+            out.push(format!(
+                "the comparison graph has {} components (sizes {:?}): rights of different components are never compared !!!",
+                self.components.len(),
+                self.components.iter().map(|c| c.len()).collect::<Vec<_>>()
+            ));
+        } else if self.relative_connectivity() < WEAK_CONNECTIVITY {
+            out.push(format!(
+                "the comparison graph is weakly connected (algebraic connectivity {:.3}, {:.3} of the average degree < {WEAK_CONNECTIVITY}, diameter {}) !!!",
+                self.algebraic_connectivity,
+                self.relative_connectivity(),
+                self.diameter.unwrap_or(0)
+            ));
+        }
+        out
+    }
+}
+
+impl fmt::Display for ComparisonReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "components: {} (sizes {:?})",
+            self.components.len(),
+            self.components.iter().map(|c| c.len()).collect::<Vec<_>>()
+        )?;
+        match self.diameter {
+            Some(d) => writeln!(f, "diameter: {d}")?,
+            None => writeln!(f, "diameter: infinite (disconnected)")?,
+        }
+        writeln!(
+            f,
+            "algebraic connectivity: {:.3} ({:.3} of the average degree {:.2})",
+            self.algebraic_connectivity,
+            self.relative_connectivity(),
+            self.average_degree
+        )?;
+        write!(f, "weakest (right, compared rights, shared lefts):")?;
+        for (r, compared, degree) in &self.weakest {
+            write!(f, " ({r}, {compared}, {degree})")?;
+        }
+        Ok(())
+    }
+}
+
+// `symmetric_eigenvalues` - ascending eigenvalues of the symmetric matrix `a` (cyclic Jacobi rotations)
+pub fn symmetric_eigenvalues(mut a: Vec<Vec<f64>>) -> Vec<f64> {
+    let n = a.len();
+    let norm: f64 = a.iter().flatten().map(|x| x * x).sum::<f64>().sqrt();
+    for _ in 0..JACOBI_SWEEPS {
+        let off: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |j| *j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum::<f64>()
+            .sqrt();
+        if off <= 1e-12 * norm.max(1.0) {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q].abs() <= 1e-15 * norm.max(1.0) {
+                    continue;
+                }
+                // rotation zeroing a[p][q]
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (rows_p, rows_q) = a.split_at_mut(q);
+                for (apk, aqk) in rows_p[p].iter_mut().zip(rows_q[0].iter_mut()) {
+                    (*apk, *aqk) = (c * *apk - s * *aqk, s * *apk + c * *aqk);
+                }
+            }
+        }
+    }
+    let mut out: Vec<f64> = (0..n).map(|i| a[i][i]).collect();
+    out.sort_by(f64::total_cmp);
+    out
+}
+
+impl Assignments {
+    // `comparison_graph` of the rights of the current assignments
+    pub fn comparison_graph(&self) -> ComparisonGraph {
+        ComparisonGraph::of(&self.get_pairs_of_ids(), self.n)
+    }
+}
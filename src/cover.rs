@@ -0,0 +1,191 @@
+// Covering mode: assignments where as many pairs of rights as possible share a left
+// (for rankings: the aggregated scores of two items are comparable when they share rankers)
+
+use crate::*;
+use std::collections::HashMap;
+
+// default number of steps of `improve_coverage` for each pair of assignments
+pub const COVER_STEPS_PER_PAIR: usize = 1000;
+
+// CoverStats
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CoverStats {
+    pub steps: usize,
+    pub accepted: usize,
+    pub covered: usize, // covered pairs of rights of the best assignments
+    pub right_pairs: usize,
+}
+
+impl fmt::Display for CoverStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} steps ({} accepted), right pairs covered {}/{} ({:.1}%)",
+            self.steps,
+            self.accepted,
+            self.covered,
+            self.right_pairs,
+            100.0 * self.covered as f64 / self.right_pairs.max(1) as f64
+        )
+    }
+}
+
+// Cover - covering pairs with the statistics of the annealing
+pub type Cover = (Vec<(usize, usize)>, CoverStats);
+
+// Coverage - how many lefts share each covered pair of rights
+struct Coverage {
+    count: HashMap<(usize, usize), usize>, // (r1, r2) with r1 < r2 -> shared lefts (only covered pairs)
+}
+
+impl Coverage {
+    fn new(rights_of_left: &[Vec<usize>]) -> Self {
+        let mut out = Self {
+            count: HashMap::new(),
+        };
+        for rights in rights_of_left {
+            for (i, &r) in rights.iter().enumerate() {
+                out.add(&rights[..i], r, usize::MAX);
+            }
+        }
+        out
+    }
+
+    // number of the covered pairs of rights
+    fn covered(&self) -> usize {
+        self.count.len()
+    }
+
+    // `add` counts the pairs of `r` with `rights` (except `r` and `skip`) once more
+    fn add(&mut self, rights: &[usize], r: usize, skip: usize) {
+        for &x in rights {
+            if x != r && x != skip {
+                *self.count.entry((r.min(x), r.max(x))).or_insert(0) += 1;
+            }
+        }
+    }
+
+    // `remove` counts the pairs of `r` with `rights` (except `r` and `skip`) once less
+    fn remove(&mut self, rights: &[usize], r: usize, skip: usize) {
+        for &x in rights {
+            if x != r && x != skip {
+                let key = (r.min(x), r.max(x));
+                if let Some(count) = self.count.get_mut(&key) {
+                    *count -= 1;
+                    if *count == 0 {
+                        self.count.remove(&key);
+                    }
+                }
+            }
+        }
+    }
+
+    // `replace` `from` by `to` in the rights of a left (the coverage of the other rights changes)
+    fn replace(&mut self, rights: &[usize], from: usize, to: usize) {
+        self.remove(rights, from, to);
+        self.add(rights, to, from);
+    }
+}
+
+// `improve_coverage` - simulated annealing over the switches and shifts of `sample::sample_uniform`
+// (never adding forbidden pairs) maximizing the number of covered pairs of rights; starts from
+// the current assignments (or from min-cost flow ones if the current contain forbidden pairs)
+pub fn improve_coverage(
+    assignments: &Assignments,
+    steps: usize,
+    rng: &mut impl Rng,
+) -> Result<Cover, Box<dyn Error>> {
+    let (k, m, n) = assignments.get_kmn();
+    if m == 0 || n == 0 {
+        return Err("no pairs to cover !!!".into());
+    }
+    let mut is_forbidden = vec![vec![false; n]; m];
+    for &(l, r) in assignments.forbidden() {
+        if l >= m || r >= n {
+            return Err(format!("forbidden ({l}, {r}) out of range !!!").into());
+        }
+        is_forbidden[l][r] = true;
+    }
+    let mut pairs = assignments.get_pairs_of_ids();
+    if assignments.number_of_forbidden_used() > 0 {
+        // the fewest forbidden pairs (the moves never add any)
+        let cost = |l: usize, r: usize| Some(is_forbidden[l][r] as i64);
+        if let Some((_, min)) = flow::min_cost_pairs(k, m, n, assignments.p(), cost) {
+            pairs = min;
+        }
+    }
+    let mut rights_of_left: Vec<Vec<usize>> = vec![vec![]; m];
+    let mut r_deg = vec![0; n];
+    for &(l, r) in &pairs {
+        rights_of_left[l].push(r);
+        r_deg[r] += 1;
+    }
+    let mut coverage = Coverage::new(&rights_of_left);
+    let mut best = (coverage.covered(), rights_of_left.clone());
+    let mut stats = CoverStats {
+        steps,
+        right_pairs: n * n.saturating_sub(1) / 2,
+        ..Default::default()
+    };
+    let allowed =
+        |rights: &[usize], l: usize, r: usize| !is_forbidden[l][r] && !rights.contains(&r);
+    for step in 0..steps {
+        let temperature = 1.0 - step as f64 / steps as f64;
+        let before = coverage.covered();
+        let l1 = rng.random_range(0..m);
+        let i1 = rng.random_range(0..rights_of_left[l1].len());
+        let r1 = rights_of_left[l1][i1];
+        // (l1, r1) -> (l1, r2) and, for a switch, (l2, r2) -> (l2, r1)
+        let mut undo: Vec<(usize, usize, usize)> = vec![]; // (left, index, old right)
+        if rng.random_bool(0.5) {
+            let l2 = rng.random_range(0..m);
+            let i2 = rng.random_range(0..rights_of_left[l2].len());
+            let r2 = rights_of_left[l2][i2];
+            if l1 == l2
+                || !allowed(&rights_of_left[l1], l1, r2)
+                || !allowed(&rights_of_left[l2], l2, r1)
+            {
+                continue;
+            }
+            coverage.replace(&rights_of_left[l1], r1, r2);
+            rights_of_left[l1][i1] = r2;
+            coverage.replace(&rights_of_left[l2], r2, r1);
+            rights_of_left[l2][i2] = r1;
+            undo.push((l1, i1, r1));
+            undo.push((l2, i2, r2));
+        } else {
+            let r2 = rng.random_range(0..n);
+            if r_deg[r1] != k + 1 || r_deg[r2] != k || !allowed(&rights_of_left[l1], l1, r2) {
+                continue;
+            }
+            coverage.replace(&rights_of_left[l1], r1, r2);
+            rights_of_left[l1][i1] = r2;
+            r_deg[r1] -= 1;
+            r_deg[r2] += 1;
+            undo.push((l1, i1, r1));
+        }
+        let delta = coverage.covered() as f64 - before as f64;
+        if delta >= 0.0 || rng.random::<f64>() < (delta / temperature.max(0.01)).exp() {
+            stats.accepted += 1;
+            if coverage.covered() > best.0 {
+                best = (coverage.covered(), rights_of_left.clone());
+            }
+            continue;
+        }
+        // rejected: undo in the reverse order
+        for &(l, i, old) in undo.iter().rev() {
+            let new = rights_of_left[l][i];
+            coverage.replace(&rights_of_left[l], new, old);
+            rights_of_left[l][i] = old;
+            r_deg[new] -= 1;
+            r_deg[old] += 1;
+        }
+    }
+    stats.covered = best.0;
+    let mut out = vec![];
+    for (l, rights) in best.1.iter().enumerate() {
+        out.extend(rights.iter().map(|r| (l, *r)));
+    }
+    out.sort();
+    Ok((out, stats))
+}
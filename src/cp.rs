@@ -0,0 +1,514 @@
+// Constraint programming solver: depth-first search over the (left, right) incidence matrix with
+// forward checking and degree propagation, `forbidden` pairs as hard zeros and pluggable side constraints
+
+use crate::progress::*;
+use crate::*;
+use std::time::{Duration, Instant};
+
+// default max number of search nodes
+pub const CP_NODE_LIMIT: usize = 1_000_000;
+
+// Cell of the incidence matrix
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cell {
+    Unknown,
+    One,  // (l, r) is assigned
+    Zero, // (l, r) is not assigned
+}
+
+// Grid - partially decided incidence matrix with the degrees of lefts and rights
+#[derive(Debug, Clone)]
+pub struct Grid {
+    k: usize,
+    m: usize,
+    n: usize,
+    p: usize,
+    cells: Vec<Cell>, // cells[l * n + r]
+    l_ones: Vec<usize>,
+    l_free: Vec<usize>, // unknown cells of each left
+    r_ones: Vec<usize>,
+    r_free: Vec<usize>,
+    full: usize,       // rights with k+1 lefts
+    trail: Vec<usize>, // decided cells (for `undo`)
+}
+
+impl Grid {
+    fn new(k: usize, m: usize, n: usize, p: usize) -> Self {
+        Self {
+            k,
+            m,
+            n,
+            p,
+            cells: vec![Cell::Unknown; m * n],
+            l_ones: vec![0; m],
+            l_free: vec![n; m],
+            r_ones: vec![0; n],
+            r_free: vec![m; n],
+            full: 0,
+            trail: vec![],
+        }
+    }
+
+    pub fn get_kmn(&self) -> (usize, usize, usize) {
+        (self.k, self.m, self.n)
+    }
+
+    pub fn cell(&self, l: usize, r: usize) -> Cell {
+        self.cells[l * self.n + r]
+    }
+
+    // lefts with (l, r) = `cell`
+    pub fn lefts(&self, r: usize, cell: Cell) -> impl Iterator<Item = usize> + '_ {
+        (0..self.m).filter(move |l| self.cell(*l, r) == cell)
+    }
+
+    // rights with (l, r) = `cell`
+    pub fn rights(&self, l: usize, cell: Cell) -> impl Iterator<Item = usize> + '_ {
+        (0..self.n).filter(move |r| self.cell(l, *r) == cell)
+    }
+
+    // number of rights assigned to both `l1` and `l2`
+    pub fn shared_rights(&self, l1: usize, l2: usize) -> usize {
+        (0..self.n)
+            .filter(|r| self.cell(l1, *r) == Cell::One && self.cell(l2, *r) == Cell::One)
+            .count()
+    }
+
+    fn set(&mut self, l: usize, r: usize, value: Cell) {
+        self.cells[l * self.n + r] = value;
+        self.l_free[l] -= 1;
+        self.r_free[r] -= 1;
+        if value == Cell::One {
+            self.l_ones[l] += 1;
+            self.r_ones[r] += 1;
+            if self.r_ones[r] == self.k + 1 {
+                self.full += 1;
+            }
+        }
+        self.trail.push(l * self.n + r);
+    }
+
+    // `undo` the cells decided after the trail had `len` cells
+    fn undo(&mut self, len: usize) {
+        while self.trail.len() > len {
+            let i = self.trail.pop().expect("trail is longer than len");
+            let (l, r) = (i / self.n, i % self.n);
+            if self.cells[i] == Cell::One {
+                if self.r_ones[r] == self.k + 1 {
+                    self.full -= 1;
+                }
+                self.l_ones[l] -= 1;
+                self.r_ones[r] -= 1;
+            }
+            self.l_free[l] += 1;
+            self.r_free[r] += 1;
+            self.cells[i] = Cell::Unknown;
+        }
+    }
+
+    // pairs of the decided ones
+    fn pairs(&self) -> Vec<(usize, usize)> {
+        let mut out = vec![];
+        for l in 0..self.m {
+            out.extend(self.rights(l, Cell::One).map(|r| (l, r)));
+        }
+        out
+    }
+
+    // `overlap_lower_bound` - lower bound of the max left overlap: the pairs of lefts sharing a right
+    // (determined by the degrees of rights) divided among all the pairs of lefts
+    fn overlap_lower_bound(&self) -> usize {
+        let plus = self.m * self.p - self.k * self.n;
+        let pairs = |d: usize| d * d.saturating_sub(1) / 2;
+        let shared = (self.n - plus) * pairs(self.k) + plus * pairs(self.k + 1);
+        div_ceil(shared, pairs(self.m).max(1))
+    }
+
+    // max number of rights shared by two lefts
+    fn max_left_overlap(&self) -> usize {
+        let mut max = 0;
+        for l1 in 0..self.m {
+            for l2 in l1 + 1..self.m {
+                max = max.max(self.shared_rights(l1, l2));
+            }
+        }
+        max
+    }
+}
+
+// SideConstraint - a constraint checked on each decided cell (besides the degree rules)
+pub trait SideConstraint {
+    fn describe(&self) -> String;
+
+    // false if `grid` (with the cell (l, r) just decided) cannot be completed satisfying the constraint
+    fn consistent(&self, grid: &Grid, l: usize, r: usize) -> bool;
+}
+
+// MaxOverlap - two lefts share at most `max` rights
+pub struct MaxOverlap(pub usize);
+
+impl SideConstraint for MaxOverlap {
+    fn describe(&self) -> String {
+        format!("max left overlap {}", self.0)
+    }
+
+    fn consistent(&self, grid: &Grid, l: usize, r: usize) -> bool {
+        grid.cell(l, r) != Cell::One
+            || grid
+                .lefts(r, Cell::One)
+                .all(|x| x == l || grid.shared_rights(l, x) <= self.0)
+    }
+}
+
+// LeftConflict - the two lefts share no right
+pub struct LeftConflict(pub usize, pub usize);
+
+impl SideConstraint for LeftConflict {
+    fn describe(&self) -> String {
+        format!("lefts {} and {} in conflict", self.0, self.1)
+    }
+
+    fn consistent(&self, grid: &Grid, l: usize, r: usize) -> bool {
+        let other = match l {
+            _ if l == self.0 => self.1,
+            _ if l == self.1 => self.0,
+            _ => return true,
+        };
+        grid.cell(l, r) != Cell::One || grid.cell(other, r) != Cell::One
+    }
+}
+
+// GroupCoverage - each right has from `min` to `max` lefts of `group`
+pub struct GroupCoverage {
+    pub group: Vec<usize>,
+    pub min: usize,
+    pub max: usize,
+}
+
+impl SideConstraint for GroupCoverage {
+    fn describe(&self) -> String {
+        format!(
+            "each right with {}..={} lefts of {:?}",
+            self.min, self.max, self.group
+        )
+    }
+
+    fn consistent(&self, grid: &Grid, l: usize, r: usize) -> bool {
+        if !self.group.contains(&l) {
+            return true;
+        }
+        let count = |cell: Cell| {
+            self.group
+                .iter()
+                .filter(|x| grid.cell(**x, r) == cell)
+                .count()
+        };
+        let ones = count(Cell::One);
+        ones <= self.max && ones + count(Cell::Unknown) >= self.min
+    }
+}
+
+// CpStatus - result of the search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CpStatus {
+    Optimal,    // the best assignments (the search space exhausted)
+    Feasible,   // assignments found, but not proved the best (the limit reached or `minimize=none`)
+    Unknown,    // the limit stopped the search before finding any assignments
+    Infeasible, // no assignments satisfy the constraints
+}
+
+impl fmt::Display for CpStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            CpStatus::Optimal => "optimal",
+            CpStatus::Feasible => "feasible",
+            CpStatus::Unknown => "unknown",
+            CpStatus::Infeasible => "infeasible",
+        };
+        write!(f, "{status}")
+    }
+}
+
+// CpOutcome
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpOutcome {
+    pub status: CpStatus,
+    pub pairs: Option<Vec<(usize, usize)>>, // the best assignments found
+    pub max_left_overlap: Option<usize>,    // of `pairs`
+    pub solutions: usize,                   // improving assignments found
+    pub nodes: usize,
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for CpOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "status {}", self.status)?;
+        if let Some(overlap) = self.max_left_overlap {
+            write!(f, ", max left overlap {overlap}")?;
+        }
+        write!(
+            f,
+            ", {} solutions, {} nodes, {:.2}s",
+            self.solutions,
+            self.nodes,
+            self.elapsed.as_secs_f64()
+        )
+    }
+}
+
+// CpSolver - assignments without forbidden pairs satisfying `constraints`,
+// with the minimal max left overlap if `minimize_overlap`
+pub struct CpSolver {
+    pub node_limit: usize,
+    pub time: Option<Duration>,
+    pub minimize_overlap: bool,
+    pub constraints: Vec<Box<dyn SideConstraint>>,
+}
+
+impl Default for CpSolver {
+    fn default() -> Self {
+        Self {
+            node_limit: CP_NODE_LIMIT,
+            time: None,
+            minimize_overlap: true,
+            constraints: vec![],
+        }
+    }
+}
+
+// state of the search
+struct Search<'a> {
+    solver: &'a CpSolver,
+    start: Instant,
+    nodes: usize,
+    stopped: bool,                              // by a limit
+    bound: Option<MaxOverlap>,                  // better than the best found
+    best: Option<(Vec<(usize, usize)>, usize)>, // pairs and their max left overlap
+    solutions: usize,
+}
+
+impl Search<'_> {
+    // `consistent` checks the side constraints (and the bound) on the decided cell (l, r)
+    fn consistent(&self, grid: &Grid, l: usize, r: usize) -> bool {
+        self.solver
+            .constraints
+            .iter()
+            .all(|c| c.consistent(grid, l, r))
+            && self.bound.as_ref().is_none_or(|c| c.consistent(grid, l, r))
+    }
+
+    // `assign` decides (l, r) = `value` and propagates the degree rules (false on a conflict)
+    fn assign(&self, grid: &mut Grid, l: usize, r: usize, value: Cell) -> bool {
+        let plus = grid.m * grid.p - grid.k * grid.n; // rights with k+1 lefts
+        let mut queue = vec![(l, r, value)];
+        while let Some((l, r, value)) = queue.pop() {
+            match grid.cell(l, r) {
+                Cell::Unknown => grid.set(l, r, value),
+                x if x == value => continue,
+                _ => return false,
+            }
+            if !self.consistent(grid, l, r) {
+                return false;
+            }
+            let (k, p) = (grid.k, grid.p);
+            // left l has p rights
+            let (ones, free) = (grid.l_ones[l], grid.l_free[l]);
+            if ones > p || ones + free < p {
+                return false;
+            }
+            if free > 0 && (ones == p || ones + free == p) {
+                let fill = if ones == p { Cell::Zero } else { Cell::One };
+                queue.extend(grid.rights(l, Cell::Unknown).map(|x| (l, x, fill)));
+            }
+            // right r has k or k+1 lefts (`plus` of them k+1)
+            let (ones, free) = (grid.r_ones[r], grid.r_free[r]);
+            if ones > k + 1 || ones + free < k || grid.full > plus {
+                return false;
+            }
+            if value == Cell::One && ones == k + 1 && grid.full == plus {
+                // no other right may have k+1 lefts
+                for x in 0..grid.n {
+                    if grid.r_ones[x] == k && grid.r_free[x] > 0 {
+                        queue.extend(grid.lefts(x, Cell::Unknown).map(|y| (y, x, Cell::Zero)));
+                    }
+                }
+            }
+            let max = if ones <= k && grid.full == plus {
+                k
+            } else {
+                k + 1
+            };
+            if free > 0 && ones == max {
+                queue.extend(grid.lefts(r, Cell::Unknown).map(|y| (y, r, Cell::Zero)));
+            } else if free > 0 && ones + free == k {
+                queue.extend(grid.lefts(r, Cell::Unknown).map(|y| (y, r, Cell::One)));
+            }
+        }
+        true
+    }
+
+    // `limit_reached` by nodes or time (or Ctrl-C)
+    fn limit_reached(&mut self) -> bool {
+        if self.nodes >= self.solver.node_limit
+            || self.nodes.is_multiple_of(256)
+                && (interrupted()
+                    || self
+                        .solver
+                        .time
+                        .is_some_and(|time| self.start.elapsed() >= time))
+        {
+            self.stopped = true;
+        }
+        self.stopped
+    }
+
+    // `next_cell` - an unknown cell of the left with the least slack, on the right
+    // with the fewest lefts sharing the most rights with it (to keep the overlaps low)
+    fn next_cell(grid: &Grid) -> Option<(usize, usize)> {
+        let l = (0..grid.m)
+            .filter(|l| grid.l_free[*l] > 0)
+            .min_by_key(|l| grid.l_free[*l] - (grid.p - grid.l_ones[*l]))?;
+        let r = grid.rights(l, Cell::Unknown).min_by_key(|r| {
+            let overlap = grid
+                .lefts(*r, Cell::One)
+                .map(|x| grid.shared_rights(l, x))
+                .max();
+            (overlap, grid.r_ones[*r])
+        })?;
+        Some((l, r))
+    }
+
+    // `dfs` returns true when the search is done (proved optimal or stopped)
+    fn dfs(&mut self, grid: &mut Grid) -> bool {
+        self.nodes += 1;
+        if self.limit_reached() {
+            return true;
+        }
+        let Some((l, r)) = Self::next_cell(grid) else {
+            // all the cells decided
+            let overlap = grid.max_left_overlap();
+            if self.best.as_ref().is_none_or(|(_, best)| overlap < *best) {
+                self.best = Some((grid.pairs(), overlap));
+                self.solutions += 1;
+                if !self.solver.minimize_overlap || overlap <= grid.overlap_lower_bound() {
+                    return true;
+                }
+                self.bound = Some(MaxOverlap(overlap - 1));
+            }
+            return false;
+        };
+        for value in [Cell::One, Cell::Zero] {
+            let len = grid.trail.len();
+            let done = self.assign(grid, l, r, value) && self.dfs(grid);
+            grid.undo(len);
+            if done {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl CpSolver {
+    // `set_parameter` of `name=value` (nodes, time, minimize, max_overlap, conflict, group)
+    pub fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        let usize_list = |value: &str, separator: char| -> Result<Vec<usize>, Box<dyn Error>> {
+            let mut out = vec![];
+            for x in value.split(separator) {
+                out.push(x.trim().parse()?);
+            }
+            Ok(out)
+        };
+        match name {
+            "nodes" => self.node_limit = value.parse()?,
+            "time" => self.time = Some(parse_duration(value)?),
+            "minimize" => match value {
+                "overlap" => self.minimize_overlap = true,
+                "none" => self.minimize_overlap = false,
+                _ => {
+                    return Err(
+                        format!("minimize `{value}` is not one of overlap, none !!!").into(),
+                    );
+                }
+            },
+            "max_overlap" => self.constraints.push(Box::new(MaxOverlap(value.parse()?))),
+            "conflict" => match usize_list(value, '-')?.as_slice() {
+                [l1, l2] => self.constraints.push(Box::new(LeftConflict(*l1, *l2))),
+                _ => return Err(format!("conflict `{value}` is not of the form L1-L2 !!!").into()),
+            },
+            "group" => {
+                let Some((group, range)) = value.split_once(':') else {
+                    return Err(format!(
+                        "group `{value}` is not of the form L1,L2,...:MIN:MAX !!!"
+                    )
+                    .into());
+                };
+                let [min, max] = usize_list(range, ':')?[..] else {
+                    return Err(format!(
+                        "group `{value}` is not of the form L1,L2,...:MIN:MAX !!!"
+                    )
+                    .into());
+                };
+                self.constraints.push(Box::new(GroupCoverage {
+                    group: usize_list(group, ',')?,
+                    min,
+                    max,
+                }));
+            }
+            _ => {
+                return Err(format!(
+                    "unknown parameter `{name}` (parameters: nodes, time, minimize, max_overlap, conflict, group) !!!"
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    // `solve` searches assignments of (k, m, n) of `assignments` without its forbidden pairs
+    pub fn solve(&self, assignments: &Assignments) -> Result<CpOutcome, Box<dyn Error>> {
+        let (k, m, n) = assignments.get_kmn();
+        for &(l, r) in assignments.forbidden() {
+            if l >= m || r >= n {
+                return Err(format!("forbidden ({l}, {r}) out of range !!!").into());
+            }
+        }
+        let _guard = SearchGuard::new(); // Ctrl-C stops the search
+        let mut search = Search {
+            solver: self,
+            start: Instant::now(),
+            nodes: 0,
+            stopped: false,
+            bound: None,
+            best: None,
+            solutions: 0,
+        };
+        let mut grid = Grid::new(k, m, n, assignments.p());
+        let forbidden_ok = assignments
+            .forbidden()
+            .iter()
+            .all(|&(l, r)| search.assign(&mut grid, l, r, Cell::Zero));
+        if forbidden_ok {
+            search.dfs(&mut grid);
+        }
+        let status = match (&search.best, search.stopped) {
+            // without minimizing the first assignments found are not proved the best
+            (Some(_), false) if self.minimize_overlap => CpStatus::Optimal,
+            (Some(_), _) => CpStatus::Feasible,
+            (None, false) => CpStatus::Infeasible,
+            (None, true) => CpStatus::Unknown,
+        };
+        let (pairs, max_left_overlap) = match search.best {
+            Some((pairs, overlap)) => (Some(pairs), Some(overlap)),
+            None => (None, None),
+        };
+        Ok(CpOutcome {
+            status,
+            pairs,
+            max_left_overlap,
+            solutions: search.solutions,
+            nodes: search.nodes,
+            elapsed: search.start.elapsed(),
+        })
+    }
+}
@@ -0,0 +1,127 @@
+// Differences of two assignments: the pairs removed and added, the lefts and rights affected
+// and the change of the number of forbidden pairs used
+
+use crate::*;
+
+// AssignmentsDiff - from `before` to `after`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssignmentsDiff {
+    pub removed: Vec<(usize, usize, bool)>, // (l, r, forbidden in `before`) only in `before`
+    pub added: Vec<(usize, usize, bool)>,   // (l, r, forbidden in `after`) only in `after`
+    pub common: usize,                      // number of pairs in both
+    pub forbidden_before: usize,            // forbidden pairs used by `before`
+    pub forbidden_after: usize,             // forbidden pairs used by `after`
+}
+
+impl AssignmentsDiff {
+    // `of` pairs with their forbidden pairs
+    pub fn of(
+        before: &[(usize, usize)],
+        forbidden_before: &[(usize, usize)],
+        after: &[(usize, usize)],
+        forbidden_after: &[(usize, usize)],
+    ) -> Self {
+        let only_in = |x: &[(usize, usize)], y: &[(usize, usize)], forbidden: &[(usize, usize)]| {
+            let mut out: Vec<(usize, usize, bool)> = x
+                .iter()
+                .filter(|p| !y.contains(p))
+                .map(|&(l, r)| (l, r, forbidden.contains(&(l, r))))
+                .collect();
+            out.sort();
+            out
+        };
+        let removed = only_in(before, after, forbidden_before);
+        let added = only_in(after, before, forbidden_after);
+        Self {
+            common: before.len() - removed.len(),
+            forbidden_before: before
+                .iter()
+                .filter(|p| forbidden_before.contains(p))
+                .count(),
+            forbidden_after: after.iter().filter(|p| forbidden_after.contains(p)).count(),
+            removed,
+            added,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+
+    // sorted lefts with removed or added pairs
+    pub fn lefts(&self) -> Vec<usize> {
+        self.affected(|(l, _, _)| *l)
+    }
+
+    // sorted rights with removed or added pairs
+    pub fn rights(&self) -> Vec<usize> {
+        self.affected(|(_, r, _)| *r)
+    }
+
+    fn affected(&self, id: impl Fn(&(usize, usize, bool)) -> usize) -> Vec<usize> {
+        let mut out: Vec<usize> = self.removed.iter().chain(&self.added).map(id).collect();
+        out.sort();
+        out.dedup();
+        out
+    }
+
+    pub fn header(&self) -> String {
+        format!(
+            "{} common pairs, {} removed, {} added, forbidden {} -> {}",
+            self.common,
+            self.removed.len(),
+            self.added.len(),
+            self.forbidden_before,
+            self.forbidden_after
+        )
+    }
+
+    // `body` - lines `- l r` (removed) and `+ l r` (added), `!!!` for forbidden, in the order of
+    // lefts (`by_rights` false) or of rights, formatted as `Assignments::assignments_body`
+    pub fn body(&self, by_rights: bool) -> String {
+        let mut lines: Vec<(usize, usize, char, bool)> = self
+            .removed
+            .iter()
+            .map(|&(l, r, f)| (l, r, '-', f))
+            .chain(self.added.iter().map(|&(l, r, f)| (l, r, '+', f)))
+            .collect();
+        if by_rights {
+            lines.sort_by_key(|&(l, r, sign, _)| (r, l, sign));
+        } else {
+            lines.sort_by_key(|&(l, r, sign, _)| (l, r, sign));
+        }
+        let mut out = String::from("  [\n");
+        for (l, r, sign, forbidden) in lines {
+            let warn = if forbidden { " !!!" } else { "" };
+            out = format!("{}  {} {} {}{}\n", out, sign, l, r, warn);
+        }
+        out = format!("{}  ]\n", out);
+        out
+    }
+}
+
+impl fmt::Display for AssignmentsDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.header())?;
+        writeln!(f, "lefts affected: {:?}", self.lefts())?;
+        writeln!(f, "rights affected: {:?}", self.rights())?;
+        write!(
+            f,
+            "by lefts (- removed, + added, !!! forbidden):\n{}",
+            self.body(false)
+        )?;
+        write!(f, "by rights:\n{}", self.body(true).trim_end())
+    }
+}
+
+impl Assignments {
+    // `diff` - changes from the current assignments to `other` (each with its forbidden pairs)
+    pub fn diff(&self, other: &Assignments) -> AssignmentsDiff {
+        AssignmentsDiff::of(
+            &self.get_pairs_of_ids(),
+            &self.forbidden,
+            &other.get_pairs_of_ids(),
+            &other.forbidden,
+        )
+    }
+}
@@ -0,0 +1,250 @@
+// Exact solver for small instances: backtracking over all assignments satisfying the degree rules
+// (each left has p rights, each right has k or k+1 lefts), minimizing the number of forbidden pairs;
+// the minimum alone is given by the min-cost flow (`forbidden_minimum`), the exhaustive search
+// adds the count of the optimal assignments and an independent check of the flow and the searches
+
+use crate::*;
+use std::collections::HashMap;
+
+// default max number of search nodes (distinct states of the backtracking)
+pub const EXACT_NODE_LIMIT: usize = 200_000;
+
+// ExactOutcome
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExactOutcome {
+    pub pairs: Vec<(usize, usize)>, // assignments with the minimal number of forbidden pairs
+    pub forbidden: usize,           // the minimal number of forbidden pairs
+    pub optimal: Option<u128>,      // number of assignments with `forbidden` pairs (if counted)
+    pub nodes: usize,               // search nodes visited
+}
+
+impl ExactOutcome {
+    // number of valid assignments (without forbidden pairs), if counted
+    pub fn valid(&self) -> Option<u128> {
+        self.optimal
+            .map(|x| if self.forbidden == 0 { x } else { 0 })
+    }
+}
+
+impl fmt::Display for ExactOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "minimal forbidden {}", self.forbidden)?;
+        if let (Some(optimal), Some(valid)) = (self.optimal, self.valid()) {
+            write!(f, ", {optimal} optimal assignments ({valid} valid)")?;
+        }
+        write!(f, ", {} nodes", self.nodes)
+    }
+}
+
+// ExactSolver - backtracking over the rights (choosing k or k+1 lefts of each right)
+// memoized by the degrees of the lefts, so the equal subproblems are solved once
+pub struct ExactSolver {
+    pub count: bool, // count all the optimal assignments (else stop at the first valid one)
+    pub node_limit: usize, // fail after visiting `node_limit` nodes
+}
+
+impl Default for ExactSolver {
+    fn default() -> Self {
+        Self {
+            count: false,
+            node_limit: EXACT_NODE_LIMIT,
+        }
+    }
+}
+
+// state of the backtracking
+struct Search<'a> {
+    solver: &'a ExactSolver,
+    k: usize,
+    n: usize,
+    p: usize,
+    forbidden: Vec<Vec<bool>>,                         // forbidden[l][r]
+    subsets: Vec<Vec<usize>>,                          // lefts of a right (sizes k and k+1)
+    memo: HashMap<(usize, Vec<usize>), (usize, u128)>, // (right, l_deg) -> (min forbidden, count)
+}
+
+impl Search<'_> {
+    // forbidden pairs of `right` with `lefts`
+    fn cost(&self, right: usize, lefts: &[usize]) -> usize {
+        lefts.iter().filter(|l| self.forbidden[**l][right]).count()
+    }
+
+    // `next_deg` - degrees of the lefts after adding `lefts` to `right`
+    // (None if the remaining rights cannot complete them)
+    fn next_deg(&self, right: usize, l_deg: &[usize], lefts: &[usize]) -> Option<Vec<usize>> {
+        let remaining = self.n - right - 1;
+        // the lefts missing more than `remaining` rights must be in `lefts`
+        // (none misses more than `remaining + 1`, checked for the previous right)
+        let urgent = |l: usize| self.p - l_deg[l] > remaining;
+        if lefts.iter().any(|l| l_deg[*l] == self.p)
+            || (0..l_deg.len()).filter(|l| urgent(*l)).count()
+                > lefts.iter().filter(|l| urgent(**l)).count()
+        {
+            return None;
+        }
+        let needed = l_deg.iter().map(|d| self.p - d).sum::<usize>() - lefts.len();
+        if needed < remaining * self.k || needed > remaining * (self.k + 1) {
+            return None;
+        }
+        let mut deg = l_deg.to_vec();
+        for &l in lefts {
+            deg[l] += 1;
+        }
+        Some(deg)
+    }
+
+    // `solve` - (min forbidden, number of completions with min forbidden) of the rights from `right`
+    // (None if there is no completion)
+    fn solve(
+        &mut self,
+        right: usize,
+        l_deg: Vec<usize>,
+    ) -> Result<Option<(usize, u128)>, Box<dyn Error>> {
+        if right == self.n {
+            return Ok(Some((0, 1)));
+        }
+        let key = (right, l_deg);
+        if let Some(x) = self.memo.get(&key) {
+            return Ok(Some(*x));
+        }
+        if self.memo.len() >= self.solver.node_limit {
+            return Err(format!(
+                "node limit {} reached (the instance is too big for the exact solver) !!!",
+                self.solver.node_limit
+            )
+            .into());
+        }
+        let mut best: Option<(usize, u128)> = None;
+        for i in 0..self.subsets.len() {
+            let cost = self.cost(right, &self.subsets[i]);
+            // bound: the completion does not decrease the cost
+            if best.is_some_and(|(min, _)| cost > min) {
+                continue;
+            }
+            let Some(deg) = self.next_deg(right, &key.1, &self.subsets[i]) else {
+                continue;
+            };
+            let Some((f, count)) = self.solve(right + 1, deg)? else {
+                continue;
+            };
+            let f = f + cost;
+            best = match best {
+                Some((min, c)) if min == f => Some((min, c.saturating_add(count))),
+                Some((min, _)) if min < f => best,
+                _ => Some((f, count)),
+            };
+            if !self.solver.count && best.is_some_and(|(min, _)| min == 0) {
+                break;
+            }
+        }
+        if let Some(x) = best {
+            self.memo.insert(key, x);
+        }
+        Ok(best)
+    }
+
+    // `pairs` of an optimal completion from `right` (after `solve`)
+    fn pairs(&self, mut l_deg: Vec<usize>) -> Vec<(usize, usize)> {
+        let mut out = vec![];
+        for right in 0..self.n {
+            let (min, _) = self.memo[&(right, l_deg.clone())];
+            let next = self.subsets.iter().find_map(|lefts| {
+                let deg = self.next_deg(right, &l_deg, lefts)?;
+                let f = if right + 1 == self.n {
+                    0
+                } else {
+                    self.memo.get(&(right + 1, deg.clone()))?.0
+                };
+                (f + self.cost(right, lefts) == min).then_some((lefts, deg))
+            });
+            let (lefts, deg) = next.expect("optimal completion is memoized");
+            out.extend(lefts.iter().map(|l| (*l, right)));
+            l_deg = deg;
+        }
+        out
+    }
+}
+
+// `subsets` of {0, ..., m-1} of `size` (in lexicographic order)
+fn subsets(m: usize, size: usize) -> Vec<Vec<usize>> {
+    let mut out = vec![];
+    extend_subsets(0, m, size, &mut vec![], &mut out);
+    out
+}
+
+// `extend_subsets` - subsets of `size` starting with `current` extended by elements >= `from`
+fn extend_subsets(
+    from: usize,
+    m: usize,
+    size: usize,
+    current: &mut Vec<usize>,
+    out: &mut Vec<Vec<usize>>,
+) {
+    if current.len() == size {
+        out.push(current.clone());
+        return;
+    }
+    for x in from..m {
+        if m - x < size - current.len() {
+            break;
+        }
+        current.push(x);
+        extend_subsets(x + 1, m, size, current, out);
+        current.pop();
+    }
+}
+
+// `binomial` (saturating)
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    let mut out: usize = 1;
+    for i in 0..k.min(n - k) {
+        out = out.saturating_mul(n - i) / (i + 1);
+    }
+    out
+}
+
+impl ExactSolver {
+    // `solve` finds assignments of `assignments` (k, m, n) with the minimal number of forbidden pairs
+    pub fn solve(&self, assignments: &Assignments) -> Result<ExactOutcome, Box<dyn Error>> {
+        let (k, m, n) = assignments.get_kmn();
+        let p = assignments.p();
+        let sizes = binomial(m, k).saturating_add(binomial(m, k + 1));
+        if sizes > self.node_limit {
+            return Err(format!(
+                "{sizes} choices of lefts for each right exceed the node limit {} !!!",
+                self.node_limit
+            )
+            .into());
+        }
+        let mut forbidden = vec![vec![false; n]; m];
+        for &(l, r) in assignments.forbidden() {
+            if l >= m || r >= n {
+                return Err(format!("forbidden ({l}, {r}) out of range !!!").into());
+            }
+            forbidden[l][r] = true;
+        }
+        let mut all = subsets(m, k);
+        all.extend(subsets(m, k + 1));
+        let mut search = Search {
+            solver: self,
+            k,
+            n,
+            p,
+            forbidden,
+            subsets: all,
+            memo: HashMap::new(),
+        };
+        let Some((f, count)) = search.solve(0, vec![0; m])? else {
+            return Err(format!("no assignments for (k, m, n) = {:?} !!!", (k, m, n)).into());
+        };
+        Ok(ExactOutcome {
+            pairs: search.pairs(vec![0; m]),
+            forbidden: f,
+            optimal: self.count.then_some(count),
+            nodes: search.memo.len(),
+        })
+    }
+}
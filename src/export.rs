@@ -0,0 +1,173 @@
+// Pictures of assignments as bipartite graphs: Graphviz DOT (the lefts and the rights as two ranks) and
+// a simple built-in SVG of the same layout (no Graphviz needed); assignment edges are gray, forbidden
+// edges red dashed, forbidden edges in the assignments red and thick; nodes show labels when available
+
+use crate::kmn_serde::*;
+use crate::*;
+use std::fs;
+
+// SVG layout: distance of the nodes of the longer side, width, margin, node radius, columns of the sides
+pub const SVG_ROW: usize = 24;
+pub const SVG_WIDTH: usize = 640;
+pub const SVG_MARGIN: usize = 40;
+pub const SVG_RADIUS: usize = 6;
+const SVG_LEFT_X: usize = 220;
+const SVG_RIGHT_X: usize = 420;
+
+// Edge - how a pair is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edge {
+    Assigned,
+    Forbidden,         // forbidden, not in the assignments
+    ForbiddenAssigned, // forbidden in the assignments
+}
+
+// `edges` - forbidden ones first so that the assignments are drawn over them
+fn edges(assignments: &Assignments) -> Vec<(usize, usize, Edge)> {
+    let pairs = assignments.get_pairs_of_ids();
+    let mut out: Vec<(usize, usize, Edge)> = assignments
+        .forbidden()
+        .iter()
+        .filter(|x| !pairs.contains(x))
+        .map(|&(l, r)| (l, r, Edge::Forbidden))
+        .collect();
+    out.sort_by_key(|&(l, r, _)| (l, r));
+    let mut assigned: Vec<(usize, usize, Edge)> = pairs
+        .iter()
+        .map(|&(l, r)| match assignments.forbidden().contains(&(l, r)) {
+            true => (l, r, Edge::ForbiddenAssigned),
+            false => (l, r, Edge::Assigned),
+        })
+        .collect();
+    assigned.sort_by_key(|&(l, r, edge)| (edge == Edge::ForbiddenAssigned, l, r));
+    out.extend(assigned);
+    out
+}
+
+// `names` of `len` ids: the labels if there is one for each id, otherwise the ids
+fn names(len: usize, labels: &[String]) -> Vec<String> {
+    match labels.len() == len {
+        true => labels.to_vec(),
+        false => (0..len).map(|x| x.to_string()).collect(),
+    }
+}
+
+fn dot_escaped(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escaped(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Assignments {
+    // `to_dot` - Graphviz DOT of the assignments (nodes `l0`, `l1`, ... and `r0`, `r1`, ...)
+    pub fn to_dot(&self, labels: &Labels) -> String {
+        let mut out = String::from("graph assignments {\n");
+        out += "  rankdir=LR;\n  ranksep=2;\n  node [shape=box, fontsize=10];\n";
+        out += &format!(
+            "  label=\"{}\";\n  labelloc=t;\n",
+            dot_escaped(&self.assignments_header())
+        );
+        for (side, prefix, len, side_labels) in [
+            ("lefts", 'l', self.m, &labels.lefts),
+            ("rights", 'r', self.n, &labels.rights),
+        ] {
+            out += &format!("  subgraph {side} {{\n    rank=same;\n");
+            for (id, name) in names(len, side_labels).iter().enumerate() {
+                out += &format!("    {prefix}{id} [label=\"{}\"];\n", dot_escaped(name));
+            }
+            out += "  }\n";
+        }
+        for (l, r, edge) in edges(self) {
+            let style = match edge {
+                Edge::Assigned => "",
+                Edge::Forbidden => " [color=red, style=dashed]",
+                Edge::ForbiddenAssigned => " [color=red, penwidth=3]",
+            };
+            out += &format!("  l{l} -- r{r}{style};\n");
+        }
+        out += "}\n";
+        out
+    }
+
+    // `to_svg` - SVG of the lefts (left column) and the rights (right column) evenly spread over
+    // the height of the longer side, with a legend of the edges
+    pub fn to_svg(&self, labels: &Labels) -> String {
+        let rows = self.m.max(self.n).max(1);
+        let top = SVG_MARGIN + 10;
+        let height = top + rows * SVG_ROW + SVG_MARGIN + 3 * SVG_ROW;
+        let y = |id: usize, len: usize| {
+            top as f64 + (id as f64 + 0.5) * (rows * SVG_ROW) as f64 / len as f64
+        };
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{SVG_WIDTH}\" height=\"{height}\" \
+             viewBox=\"0 0 {SVG_WIDTH} {height}\" font-family=\"sans-serif\" font-size=\"12\">\n"
+        );
+        out += &format!(
+            "  <rect width=\"{SVG_WIDTH}\" height=\"{height}\" fill=\"white\"/>\n  \
+             <text x=\"{}\" y=\"{SVG_MARGIN}\" text-anchor=\"middle\" font-size=\"14\">{}</text>\n",
+            SVG_WIDTH / 2,
+            xml_escaped(&self.assignments_header())
+        );
+        let stroke = |edge: Edge| match edge {
+            Edge::Assigned => "stroke=\"#777\" stroke-width=\"1\"",
+            Edge::Forbidden => "stroke=\"red\" stroke-width=\"1\" stroke-dasharray=\"4 3\"",
+            Edge::ForbiddenAssigned => "stroke=\"red\" stroke-width=\"3\"",
+        };
+        for (l, r, edge) in edges(self) {
+            out += &format!(
+                "  <line x1=\"{SVG_LEFT_X}\" y1=\"{:.1}\" x2=\"{SVG_RIGHT_X}\" y2=\"{:.1}\" {}/>\n",
+                y(l, self.m),
+                y(r, self.n),
+                stroke(edge)
+            );
+        }
+        for (x, len, side_labels, anchor, dx) in [
+            (SVG_LEFT_X, self.m, &labels.lefts, "end", -10),
+            (SVG_RIGHT_X, self.n, &labels.rights, "start", 10),
+        ] {
+            for (id, name) in names(len, side_labels).iter().enumerate() {
+                let y = y(id, len);
+                out += &format!(
+                    "  <circle cx=\"{x}\" cy=\"{y:.1}\" r=\"{SVG_RADIUS}\" fill=\"white\" stroke=\"black\"/>\n  \
+                     <text x=\"{}\" y=\"{:.1}\" text-anchor=\"{anchor}\">{}</text>\n",
+                    x as i64 + dx,
+                    y + 4.0,
+                    xml_escaped(name)
+                );
+            }
+        }
+        let legend = [
+            (Edge::Assigned, "assigned"),
+            (Edge::Forbidden, "forbidden"),
+            (Edge::ForbiddenAssigned, "forbidden in assignments"),
+        ];
+        for (i, (edge, text)) in legend.iter().enumerate() {
+            let y = top + rows * SVG_ROW + SVG_MARGIN / 2 + i * SVG_ROW;
+            out += &format!(
+                "  <line x1=\"{SVG_MARGIN}\" y1=\"{y}\" x2=\"{}\" y2=\"{y}\" {}/>\n  \
+                 <text x=\"{}\" y=\"{}\">{text}</text>\n",
+                SVG_MARGIN + 40,
+                stroke(*edge),
+                SVG_MARGIN + 50,
+                y + 4
+            );
+        }
+        out += "</svg>\n";
+        out
+    }
+
+    // `write_picture` - DOT (or SVG if `path` ends with `.svg`) of the assignments to `path`
+    pub fn write_picture(&self, labels: &Labels, path: &str) -> Result<(), Box<dyn Error>> {
+        let out = match path.ends_with(".svg") {
+            true => self.to_svg(labels),
+            false => self.to_dot(labels),
+        };
+        fs::write(path, out).map_err(|err| format!("{path}: {err}"))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+// Min-cost flow and the assignments of minimal cost it gives (e.g. the minimum of forbidden pairs)
+
+use std::collections::VecDeque;
+
+// Edge of the residual graph (the reverse edge of edge `i` is `i ^ 1`)
+#[derive(Debug, Clone)]
+struct Edge {
+    to: usize,
+    cap: i64,
+    cost: i64,
+}
+
+// MinCostFlow - successive shortest paths (Bellman-Ford queue, so negative costs are allowed)
+#[derive(Debug, Clone, Default)]
+pub struct MinCostFlow {
+    edges: Vec<Edge>,
+    graph: Vec<Vec<usize>>, // edges from each node
+}
+
+impl MinCostFlow {
+    pub fn new(nodes: usize) -> Self {
+        Self {
+            edges: vec![],
+            graph: vec![vec![]; nodes],
+        }
+    }
+
+    // `add_edge` returns the index of the edge (for `flow`)
+    pub fn add_edge(&mut self, from: usize, to: usize, cap: i64, cost: i64) -> usize {
+        let i = self.edges.len();
+        self.edges.push(Edge { to, cap, cost });
+        self.edges.push(Edge {
+            to: from,
+            cap: 0,
+            cost: -cost,
+        });
+        self.graph[from].push(i);
+        self.graph[to].push(i + 1);
+        i
+    }
+
+    // flow on the edge `i`
+    pub fn flow(&self, i: usize) -> i64 {
+        self.edges[i + 1].cap
+    }
+
+    // `run` sends at most `max_flow` from `source` to `sink`, returns (flow, cost)
+    pub fn run(&mut self, source: usize, sink: usize, max_flow: i64) -> (i64, i64) {
+        let (mut flow, mut cost) = (0, 0);
+        while flow < max_flow {
+            // shortest path in the residual graph
+            let mut dist = vec![i64::MAX; self.graph.len()];
+            let mut prev = vec![usize::MAX; self.graph.len()]; // edge to the node
+            let mut queued = vec![false; self.graph.len()];
+            let mut queue = VecDeque::from([source]);
+            dist[source] = 0;
+            while let Some(x) = queue.pop_front() {
+                queued[x] = false;
+                for &i in &self.graph[x] {
+                    let edge = &self.edges[i];
+                    if edge.cap > 0 && dist[x] + edge.cost < dist[edge.to] {
+                        dist[edge.to] = dist[x] + edge.cost;
+                        prev[edge.to] = i;
+                        if !queued[edge.to] {
+                            queued[edge.to] = true;
+                            queue.push_back(edge.to);
+                        }
+                    }
+                }
+            }
+            if dist[sink] == i64::MAX {
+                break;
+            }
+            // augment along the path
+            let mut push = max_flow - flow;
+            let mut x = sink;
+            while x != source {
+                let i = prev[x];
+                push = push.min(self.edges[i].cap);
+                x = self.edges[i ^ 1].to;
+            }
+            let mut x = sink;
+            while x != source {
+                let i = prev[x];
+                self.edges[i].cap -= push;
+                self.edges[i ^ 1].cap += push;
+                x = self.edges[i ^ 1].to;
+            }
+            flow += push;
+            cost += push * dist[sink];
+        }
+        (flow, cost)
+    }
+}
+
+// `min_cost_pairs` - pairs with left degrees `p` and right degrees k or k+1 of the minimal sum of
+// `cost(l, r)` (None for (l, r) not allowed); returns (cost, pairs) or None if there are no such pairs
+pub fn min_cost_pairs(
+    k: usize,
+    m: usize,
+    n: usize,
+    p: usize,
+    cost: impl Fn(usize, usize) -> Option<i64>,
+) -> Option<(i64, Vec<(usize, usize)>)> {
+    // nodes: source, lefts, rights, sink
+    let (source, sink) = (0, m + n + 1);
+    let mut flow = MinCostFlow::new(m + n + 2);
+    let mut pair_edges = vec![];
+    let mut max_cost = 0;
+    for l in 0..m {
+        flow.add_edge(source, 1 + l, p as i64, 0);
+        for r in 0..n {
+            if let Some(c) = cost(l, r) {
+                max_cost += c.abs();
+                pair_edges.push((flow.add_edge(1 + l, 1 + m + r, 1, c), l, r));
+            }
+        }
+    }
+    // each right takes k units first, the (m*p - k*n) extra units are expensive
+    let extra_cost = 2 * max_cost + 1;
+    let mut extra_edges = vec![];
+    for r in 0..n {
+        flow.add_edge(1 + m + r, sink, k as i64, 0);
+        extra_edges.push(flow.add_edge(1 + m + r, sink, 1, extra_cost));
+    }
+    let total = (m * p) as i64;
+    let (sent, c) = flow.run(source, sink, total);
+    let extra: i64 = extra_edges.iter().map(|i| flow.flow(*i)).sum();
+    // some right with less than k lefts
+    if sent < total || extra != total - (k * n) as i64 {
+        return None;
+    }
+    let pairs = pair_edges
+        .iter()
+        .filter(|(i, _, _)| flow.flow(*i) == 1)
+        .map(|(_, l, r)| (*l, *r))
+        .collect();
+    Some((c - extra * extra_cost, pairs))
+}
@@ -0,0 +1,91 @@
+// Bounded undo/redo history of the states edited in menus
+
+// default max number of steps kept in `History`
+pub const HISTORY_MAX: usize = 100;
+
+// History
+#[derive(Debug)]
+pub struct History<T: Clone + PartialEq> {
+    steps: Vec<(String, T)>, // (command name, state after the command)
+    current: usize,          // index of the current state in `steps`
+    max: usize,              // max number of steps kept (the oldest are dropped)
+}
+
+impl<T: Clone + PartialEq> History<T> {
+    // history starting with `state`
+    pub fn new(state: &T, max: usize) -> Self {
+        Self {
+            steps: vec![(String::from("start"), state.clone())],
+            current: 0,
+            max: max.max(1),
+        }
+    }
+
+    // `record` adds `state` after the command `cmd` if it differs from the current state
+    // (the steps that could be redone are dropped) and returns true if it was added
+    pub fn record(&mut self, cmd: &str, state: &T) -> bool {
+        if self.steps[self.current].1 == *state {
+            return false;
+        }
+        self.steps.truncate(self.current + 1);
+        self.steps.push((cmd.to_string(), state.clone()));
+        if self.steps.len() > self.max {
+            let drop = self.steps.len() - self.max;
+            self.steps.drain(0..drop);
+        }
+        self.current = self.steps.len() - 1;
+        true
+    }
+
+    // `undo` returns the state before the current step (if any)
+    pub fn undo(&mut self) -> Option<T> {
+        if self.current == 0 {
+            return None;
+        }
+        self.current -= 1;
+        Some(self.steps[self.current].1.clone())
+    }
+
+    // `redo` returns the state after the current step (if any)
+    pub fn redo(&mut self) -> Option<T> {
+        if self.current + 1 >= self.steps.len() {
+            return None;
+        }
+        self.current += 1;
+        Some(self.steps[self.current].1.clone())
+    }
+
+    // name of the command of the current step
+    pub fn current_cmd(&self) -> &str {
+        &self.steps[self.current].0
+    }
+
+    pub fn steps(&self) -> &Vec<(String, T)> {
+        &self.steps
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    // `lines` - one line for each step described by `describe` with `<-` marking the current one
+    pub fn lines<F: Fn(&T) -> String>(&self, describe: F) -> String {
+        let mut out = String::new();
+        for (i, (cmd, state)) in self.steps.iter().enumerate() {
+            let mark = if i == self.current {
+                "  <- current"
+            } else {
+                ""
+            };
+            out = format!(
+                "{}  {:>3}  {:<10} {}{}\n",
+                out,
+                i,
+                cmd,
+                describe(state),
+                mark
+            );
+        }
+        out
+    }
+}
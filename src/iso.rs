@@ -0,0 +1,274 @@
+// Isomorphism of assignments as bipartite graphs (lefts map to lefts, rights to rights) and their
+// canonical form: color refinement with individualization, the smallest relabeled pairs over the search tree
+
+use crate::*;
+use std::str::FromStr;
+
+// max number of nodes of the search tree of `canonical_form`
+pub const ISO_NODE_LIMIT: usize = 100_000;
+
+// Fixed - the sides keeping their labels (the isomorphisms may relabel only the other sides)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Fixed {
+    #[default]
+    None,
+    Lefts,
+    Rights,
+    Both,
+}
+
+impl Fixed {
+    fn lefts(&self) -> bool {
+        matches!(self, Fixed::Lefts | Fixed::Both)
+    }
+
+    fn rights(&self) -> bool {
+        matches!(self, Fixed::Rights | Fixed::Both)
+    }
+}
+
+impl FromStr for Fixed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Fixed::None),
+            "lefts" => Ok(Fixed::Lefts),
+            "rights" => Ok(Fixed::Rights),
+            "both" => Ok(Fixed::Both),
+            _ => Err(format!(
+                "unknown fixed `{s}` (fixed: none, lefts, rights, both) !!!"
+            )),
+        }
+    }
+}
+
+// CanonicalForm - the relabeled pairs equal for exactly the isomorphic assignments
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CanonicalForm {
+    pub m: usize,
+    pub n: usize,
+    pub pairs: Vec<(usize, usize)>,
+    pub nodes: usize, // nodes of the search tree
+}
+
+impl CanonicalForm {
+    // `hash` - FNV-1a of (m, n, pairs), stable across runs and platforms
+    pub fn hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let values = [self.m, self.n]
+            .into_iter()
+            .chain(self.pairs.iter().flat_map(|(l, r)| [*l, *r]));
+        for value in values {
+            for byte in (value as u64).to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        hash
+    }
+}
+
+impl fmt::Display for CanonicalForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.hash())
+    }
+}
+
+// Graph - vertices 0..m are the lefts, m..m+n the rights
+struct Graph {
+    m: usize,
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    fn new(m: usize, n: usize, pairs: &[(usize, usize)]) -> Self {
+        let mut neighbors = vec![vec![]; m + n];
+        for &(l, r) in pairs {
+            neighbors[l].push(m + r);
+            neighbors[m + r].push(l);
+        }
+        for x in &mut neighbors {
+            x.sort_unstable();
+        }
+        Self { m, neighbors }
+    }
+
+    // `refine` colors until stable: the new colors are the ranks of (color, sorted colors of neighbors),
+    // so they do not depend on the labels
+    fn refine(&self, colors: &mut [usize]) {
+        let mut number = count_colors(colors);
+        loop {
+            let signatures: Vec<(usize, Vec<usize>)> = (0..colors.len())
+                .map(|v| {
+                    let mut around: Vec<usize> =
+                        self.neighbors[v].iter().map(|x| colors[*x]).collect();
+                    around.sort_unstable();
+                    (colors[v], around)
+                })
+                .collect();
+            let mut sorted: Vec<&(usize, Vec<usize>)> = signatures.iter().collect();
+            sorted.sort();
+            sorted.dedup();
+            for (v, signature) in signatures.iter().enumerate() {
+                colors[v] = sorted.binary_search(&signature).unwrap_or(0);
+            }
+            let refined = sorted.len();
+            if refined == number {
+                return;
+            }
+            number = refined;
+        }
+    }
+
+    // `relabeled` pairs of the discrete coloring (lefts get colors 0..m, rights m..m+n)
+    fn relabeled(&self, colors: &[usize]) -> Vec<(usize, usize)> {
+        let mut out = vec![];
+        for l in 0..self.m {
+            for &r in &self.neighbors[l] {
+                out.push((colors[l], colors[r] - self.m));
+            }
+        }
+        out.sort_unstable();
+        out
+    }
+}
+
+fn count_colors(colors: &[usize]) -> usize {
+    let mut sorted = colors.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    sorted.len()
+}
+
+// Search - the smallest relabeled pairs over the leaves of the individualization tree
+struct Search<'a> {
+    graph: &'a Graph,
+    best: Option<Vec<(usize, usize)>>,
+    nodes: usize,
+    node_limit: usize,
+}
+
+impl Search<'_> {
+    fn visit(&mut self, mut colors: Vec<usize>) -> Result<(), Box<dyn Error>> {
+        self.nodes += 1;
+        if self.nodes > self.node_limit {
+            return Err(format!(
+                "canonical form: node limit {} reached (too symmetric assignments) !!!",
+                self.node_limit
+            )
+            .into());
+        }
+        self.graph.refine(&mut colors);
+        // the target cell: the smallest color class with more vertices (the smallest color first)
+        let mut sizes = vec![0; colors.len()];
+        for &c in &colors {
+            sizes[c] += 1;
+        }
+        let target = (0..sizes.len())
+            .filter(|c| sizes[*c] > 1)
+            .min_by_key(|c| (sizes[*c], *c));
+        let Some(target) = target else {
+            let pairs = self.graph.relabeled(&colors);
+            if self.best.as_ref().is_none_or(|best| pairs < *best) {
+                self.best = Some(pairs);
+            }
+            return Ok(());
+        };
+        // individualize each vertex of the target cell in turn, except twins (with the same neighbors)
+        // of earlier ones: swapping twins is an automorphism keeping the colors, so they give the same leaves
+        let cell: Vec<usize> = (0..colors.len()).filter(|x| colors[*x] == target).collect();
+        for (i, &v) in cell.iter().enumerate() {
+            let neighbors = &self.graph.neighbors[v];
+            if cell[..i]
+                .iter()
+                .any(|u| self.graph.neighbors[*u] == *neighbors)
+            {
+                continue;
+            }
+            let child = (0..colors.len())
+                .map(|x| 2 * colors[x] + (colors[x] == target && x != v) as usize)
+                .collect();
+            self.visit(child)?;
+        }
+        Ok(())
+    }
+}
+
+// `canonical_form` of the bipartite graph of `pairs` (lefts in 0..m, rights in 0..n) with the `fixed` sides
+pub fn canonical_form(
+    m: usize,
+    n: usize,
+    pairs: &[(usize, usize)],
+    fixed: Fixed,
+    node_limit: usize,
+) -> Result<CanonicalForm, Box<dyn Error>> {
+    let graph = Graph::new(m, n, pairs);
+    // lefts before rights; a fixed vertex gets its own color in the order of its label
+    let colors: Vec<usize> = (0..m + n)
+        .map(|v| match v < m {
+            true if fixed.lefts() => v,
+            true => 0,
+            false if fixed.rights() => m + v,
+            false => 2 * m + n,
+        })
+        .collect();
+    let mut search = Search {
+        graph: &graph,
+        best: None,
+        nodes: 0,
+        node_limit,
+    };
+    search.visit(colors)?;
+    Ok(CanonicalForm {
+        m,
+        n,
+        pairs: search.best.unwrap_or_default(),
+        nodes: search.nodes,
+    })
+}
+
+impl Assignments {
+    // `canonical_form` of the current assignments
+    pub fn canonical_form(&self, fixed: Fixed) -> Result<CanonicalForm, Box<dyn Error>> {
+        canonical_form(
+            self.m,
+            self.n,
+            &self.get_pairs_of_ids(),
+            fixed,
+            ISO_NODE_LIMIT,
+        )
+    }
+
+    // `is_isomorphic` - the current assignments and `pairs` (of the same (k, m, n)) are the same
+    // bipartite graph up to relabeling of the sides not `fixed`
+    pub fn is_isomorphic(
+        &self,
+        pairs: &[(usize, usize)],
+        fixed: Fixed,
+    ) -> Result<bool, Box<dyn Error>> {
+        let (mut mine, mut other) = (self.get_pairs_of_ids(), pairs.to_vec());
+        mine.sort_unstable();
+        other.sort_unstable();
+        if mine == other {
+            return Ok(true);
+        }
+        if degree_profile(self.m, self.n, &mine) != degree_profile(self.m, self.n, &other) {
+            return Ok(false);
+        }
+        let other = canonical_form(self.m, self.n, &other, fixed, ISO_NODE_LIMIT)?;
+        Ok(self.canonical_form(fixed)?.pairs == other.pairs)
+    }
+}
+
+// sorted degrees of the lefts and of the rights (equal for isomorphic graphs)
+fn degree_profile(m: usize, n: usize, pairs: &[(usize, usize)]) -> (Vec<usize>, Vec<usize>) {
+    let (mut l_deg, mut r_deg) = (vec![0; m], vec![0; n]);
+    for &(l, r) in pairs {
+        l_deg[l] += 1;
+        r_deg[r] += 1;
+    }
+    l_deg.sort_unstable();
+    r_deg.sort_unstable();
+    (l_deg, r_deg)
+}
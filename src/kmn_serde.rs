@@ -1,5 +1,24 @@
 use crate::Assignments;
+use crate::skeleton::SkeletonKind;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+
+// Labels - optional names of the lefts and of the rights (used by `merge::merge` to match ids)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Labels {
+    #[serde(default)]
+    pub lefts: Vec<String>,
+    #[serde(default)]
+    pub rights: Vec<String>,
+}
+
+impl Labels {
+    pub fn is_empty(&self) -> bool {
+        self.lefts.is_empty() && self.rights.is_empty()
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SerdeKmnAssignment {
@@ -8,6 +27,18 @@ pub struct SerdeKmnAssignment {
     n: usize, // len of r_permutation
     assignments: Vec<(usize, usize)>,
     forbidden: Vec<(usize, usize)>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    snapshots: BTreeMap<String, Vec<(usize, usize)>>, // named assignments (`snap` command)
+    #[serde(default, skip_serializing_if = "SkeletonKind::is_cyclic")]
+    skeleton: SkeletonKind, // family of the default assignments
+    #[serde(default, skip_serializing_if = "Labels::is_empty")]
+    labels: Labels,
+}
+
+impl SerdeKmnAssignment {
+    pub fn labels(&self) -> &Labels {
+        &self.labels
+    }
 }
 
 impl From<&Assignments> for SerdeKmnAssignment {
@@ -18,6 +49,9 @@ impl From<&Assignments> for SerdeKmnAssignment {
             n: item.n,
             assignments: item.get_pairs_of_ids(),
             forbidden: item.forbidden.clone(),
+            snapshots: item.snapshots.clone(),
+            skeleton: item.skeleton,
+            labels: item.labels.clone(),
         }
     }
 }
@@ -26,11 +60,46 @@ impl From<&SerdeKmnAssignment> for Assignments {
     fn from(item: &SerdeKmnAssignment) -> Self {
         let mut out = Self::new(item.k, item.m, item.n);
         out.forbidden = item.forbidden.clone();
+        // snapshots with pairs outside m x n could not be measured nor restored
+        out.snapshots = item.snapshots.clone();
+        out.snapshots.retain(|name, pairs| {
+            let valid = pairs.iter().all(|(l, r)| *l < item.m && *r < item.n);
+            if !valid {
+                eprintln!("#WARNING# Snapshot `{name}` with pairs outside m x n dropped !!!");
+            }
+            valid
+        });
+        out.skeleton = item.skeleton;
+        out.labels = item.labels.clone();
         out.set_pairs_of_ids(&item.assignments);
         out // returns not testeted !!!
     }
 }
 
+// read one JSON assignments data from the file at `path` (not tested !!!)
+pub fn read_assignments_file(path: &str) -> Result<Assignments, Box<dyn Error>> {
+    Ok(Assignments::from(&read_serde_file(path)?))
+}
+
+// read one JSON assignments data with its labels from the file at `path` (not tested !!!)
+pub fn read_serde_file(path: &str) -> Result<SerdeKmnAssignment, Box<dyn Error>> {
+    let input = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&input)?)
+}
+
+// write one-line JSON assignments data to the file at `path`
+pub fn write_assignments_file(assignments: &Assignments, path: &str) -> Result<(), Box<dyn Error>> {
+    let out = serde_json::to_string(&SerdeKmnAssignment::from(assignments))?;
+    fs::write(path, out + "\n")?;
+    Ok(())
+}
+
+// write one-line JSON assignments data with its labels to the file at `path`
+pub fn write_serde_file(data: &SerdeKmnAssignment, path: &str) -> Result<(), Box<dyn Error>> {
+    fs::write(path, serde_json::to_string(data)? + "\n")?;
+    Ok(())
+}
+
 /*
 #[derive(Serialize, Deserialize)]
 Vec<(usize,usize)>
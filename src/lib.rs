@@ -1,8 +1,37 @@
 use serde::{Deserialize, Serialize};
+pub mod cli;
 pub mod cmd;
+pub mod comparison;
+pub mod cover;
+pub mod cp;
+pub mod diff;
+pub mod exact;
+pub mod export;
+pub mod flow;
+pub mod history;
+pub mod iso;
 pub mod kmn_serde;
 pub mod menu;
+pub mod merge;
+pub mod metrics;
+pub mod pool;
+pub mod portfolio;
+pub mod progress;
+pub mod repair;
+pub mod replay;
+pub mod resize;
+pub mod sample;
+pub mod skeleton;
+pub mod split;
+pub mod strategy;
+pub mod trace;
+use kmn_serde::Labels;
+use metrics::*;
+use pool::*;
 use rand::Rng;
+use skeleton::*;
+use std::cell::Cell;
+use std::collections::BTreeMap;
 use std::convert::From;
 use std::error::Error;
 use std::fmt;
@@ -182,7 +211,7 @@ impl Pair {
 }
 
 // Pairs
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Pairs(Vec<Pair>);
 
 impl From<&Vec<(usize, usize)>> for Pairs {
@@ -312,7 +341,7 @@ impl Pairs {
 }
 
 // Permutation
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Permutation(Vec<usize>);
 
 impl Permutation {
@@ -350,7 +379,7 @@ impl Permutation {
 }
 
 // Assignments
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Assignments {
     k: usize,
     m: usize, // len of l_permutation
@@ -360,6 +389,24 @@ pub struct Assignments {
     r_permutation: Permutation,
     forbidden: Vec<(usize, usize)>,
     f_min_backup: Option<Vec<(usize, usize)>>, // may be not valid if `forbidden` change !!!
+    f_min_backup_version: usize, // `forbidden_version` the backup was evaluated against
+    forbidden_version: usize,    // incremented on each change of `forbidden`
+    snapshots: BTreeMap<String, Vec<(usize, usize)>>, // named assignments pairs (`snap` command)
+    pool: SolutionPool,          // best distinct assignments found by the searches
+    skeleton: SkeletonKind,      // family of the default assignments
+    forbidden_minimum: MinimumCache, // `forbidden_minimum` for `forbidden_version`
+    labels: Labels,              // optional names of the lefts and of the rights
+}
+
+// MinimumCache - (`forbidden_version`, `forbidden_minimum`) of the last evaluation; only a cache,
+// so it does not take part in the comparison of assignments
+#[derive(Debug, Clone, Default)]
+struct MinimumCache(Cell<Option<(usize, usize)>>);
+
+impl PartialEq for MinimumCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
 }
 
 impl fmt::Display for Assignments {
@@ -388,6 +435,13 @@ impl Assignments {
             r_permutation,
             forbidden,
             f_min_backup,
+            f_min_backup_version: 0,
+            forbidden_version: 0,
+            snapshots: BTreeMap::new(),
+            pool: SolutionPool::new(POOL_CAPACITY),
+            skeleton: SkeletonKind::Cyclic,
+            forbidden_minimum: MinimumCache::default(),
+            labels: Labels::default(),
         }
     }
 
@@ -415,6 +469,13 @@ impl Assignments {
             r_permutation,
             forbidden,
             f_min_backup,
+            f_min_backup_version: 0,
+            forbidden_version: 0,
+            snapshots: BTreeMap::new(),
+            pool: SolutionPool::new(POOL_CAPACITY),
+            skeleton: SkeletonKind::Cyclic,
+            forbidden_minimum: MinimumCache::default(),
+            labels: Labels::default(),
         }
     }
 
@@ -456,30 +517,8 @@ impl Assignments {
         k: Option<usize>,
         p: Option<usize>,
     ) -> Result<Self, Box<dyn Error>> {
-        let mut left_ids = left_ids.clone();
-        // test left_ids
-        let test_result = self.sort_and_test_subset_of_left_ids(&mut left_ids);
-        let map: Vec<Option<usize>>;
-        match test_result {
-            Err(err) => {
-                return Err(err.into());
-            }
-            Ok(map1) => {
-                map = map1;
-            }
-        }
-        let (m, n) = (left_ids.len(), self.n);
-        // test (k,m,n,p)
-        if let Err(err) = Pairs::kmnp_pairs(k, m, n, p) {
-            return Err(err.into());
-        }
-        let mut assignments = Assignments::new_kmnp(k, m, n, p);
-        for (l, r) in &self.forbidden {
-            if let Some(l1) = map[*l] {
-                assignments.forbidden.push((l1, *r));
-            }
-        }
-        Ok(assignments)
+        let right_ids: Vec<usize> = (0..self.n).collect();
+        self.reduced_to(left_ids, &right_ids, k, p)
     }
 
     // sorts and tests right_ids and creates remapping of the right ids to {0, ..., right_ids.len()-1}
@@ -520,27 +559,34 @@ impl Assignments {
         k: Option<usize>,
         p: Option<usize>,
     ) -> Result<Self, Box<dyn Error>> {
-        let mut right_ids = right_ids.clone();
-        // test right_ids
-        let test_result = self.sort_and_test_subset_of_right_ids(&mut right_ids);
-        let map: Vec<Option<usize>>;
-        match test_result {
-            Err(err) => {
-                return Err(err.into());
-            }
-            Ok(map1) => {
-                map = map1;
-            }
-        }
-        let (m, n) = (self.m, right_ids.len());
+        let left_ids: Vec<usize> = (0..self.m).collect();
+        self.reduced_to(&left_ids, right_ids, k, p)
+    }
+
+    // try to create Assignments with left and right ids reduced to sorted left_ids and right_ids
+    // with forbidden pairs of both kept ids remapped to their new ids
+    pub fn reduced_to(
+        &self,
+        left_ids: &[usize],
+        right_ids: &[usize],
+        k: Option<usize>,
+        p: Option<usize>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let (mut left_ids, mut right_ids) = (left_ids.to_vec(), right_ids.to_vec());
+        // test left_ids and right_ids
+        let left_map = self.sort_and_test_subset_of_left_ids(&mut left_ids)?;
+        let right_map = self.sort_and_test_subset_of_right_ids(&mut right_ids)?;
+        let (m, n) = (left_ids.len(), right_ids.len());
         // test (k,m,n,p)
         if let Err(err) = Pairs::kmnp_pairs(k, m, n, p) {
             return Err(err.into());
         }
         let mut assignments = Assignments::new_kmnp(k, m, n, p);
         for (l, r) in &self.forbidden {
-            if let Some(r1) = map[*r] {
-                assignments.forbidden.push((*l, r1));
+            // forbidden pairs outside m x n have no ids in the reduced assignments
+            let mapped = (left_map.get(*l).copied(), right_map.get(*r).copied());
+            if let (Some(Some(l1)), Some(Some(r1))) = mapped {
+                assignments.forbidden.push((l1, r1));
             }
         }
         Ok(assignments)
@@ -560,11 +606,24 @@ impl Assignments {
         &self.forbidden
     }
 
+    pub fn labels(&self) -> &Labels {
+        &self.labels
+    }
+
+    pub fn set_labels(&mut self, labels: Labels) {
+        self.labels = labels;
+    }
+
     pub fn assignments_header(&self) -> String {
+        let skeleton = match self.skeleton {
+            SkeletonKind::Cyclic => String::new(),
+            kind => format!(" [skeleton {kind}]"),
+        };
         format!(
-            "Assignments (k,m,n) = {:?} [p = {}]",
+            "Assignments (k,m,n) = {:?} [p = {}]{}",
             self.get_kmn(),
-            self.p()
+            self.p(),
+            skeleton
         )
     }
 
@@ -595,7 +654,43 @@ impl Assignments {
     pub fn forbidden_header(&self) -> String {
         let count = intersection_size(&self.get_pairs_of_ids(), &self.forbidden);
         let warn = if count > 0 { " !!!" } else { "" };
-        format!("Forbidden ({} / {}){}", &self.forbidden.len(), count, warn)
+        // the minimum tells whether zero forbidden may be reached
+        let minimum = match count {
+            0 => String::new(),
+            _ => format!(" [minimum {}]", self.forbidden_minimum()),
+        };
+        format!(
+            "Forbidden ({} / {}){}{}",
+            &self.forbidden.len(),
+            count,
+            minimum,
+            warn
+        )
+    }
+
+    // `forbidden_minimum` - the minimal number of forbidden pairs in any assignments of (k, m, n)
+    // (exact, by min-cost flow; the searches permuting the ids may not reach it), cached for
+    // `forbidden_version`; forbidden pairs outside m x n are ignored
+    pub fn forbidden_minimum(&self) -> usize {
+        if let Some((version, minimum)) = self.forbidden_minimum.0.get()
+            && version == self.forbidden_version
+        {
+            return minimum;
+        }
+        let (k, m, n) = self.get_kmn();
+        let mut is_forbidden = vec![vec![false; n]; m];
+        for &(l, r) in self.forbidden.iter().filter(|(l, r)| *l < m && *r < n) {
+            is_forbidden[l][r] = true;
+        }
+        let cost = |l: usize, r: usize| Some(is_forbidden[l][r] as i64);
+        let minimum = match flow::min_cost_pairs(k, m, n, self.p(), cost) {
+            Some((f, _)) => f as usize,
+            None => 0,
+        };
+        self.forbidden_minimum
+            .0
+            .set(Some((self.forbidden_version, minimum)));
+        minimum
     }
 
     pub fn forbidden_body(&self) -> String {
@@ -615,8 +710,13 @@ impl Assignments {
     pub fn backup_header(&self) -> String {
         if let Some(backup) = &self.f_min_backup {
             format!(
-                "{}-forbidden backup",
-                intersection_size(&backup, &self.forbidden)
+                "{}-forbidden backup{}",
+                intersection_size(&backup, &self.forbidden),
+                if self.backup_is_stale() {
+                    " (stale: chosen before forbidden changed)"
+                } else {
+                    ""
+                }
             )
         } else {
             format!("No backup")
@@ -635,8 +735,31 @@ impl Assignments {
         }
     }
 
+    pub fn forbidden_version(&self) -> usize {
+        self.forbidden_version
+    }
+
+    // `backup_is_stale` - the backup was chosen for an old version of `forbidden`
+    pub fn backup_is_stale(&self) -> bool {
+        self.f_min_backup.is_some() && self.f_min_backup_version != self.forbidden_version
+    }
+
+    // `rescore` re-evaluates the backup (the current assignments replace it if better)
+    // and the solution pool against the current `forbidden`
+    // returns the number of pool entries with changed number of forbidden pairs
+    pub fn rescore(&mut self) -> usize {
+        if self.f_min_backup.is_some() {
+            self.f_min_backup_update(self.get_pairs_of_ids());
+        }
+        let (m, n, version) = (self.m, self.n, self.forbidden_version);
+        let forbidden = &self.forbidden;
+        self.pool
+            .rescore(|pairs| Metrics::of(pairs, forbidden, m, n), version)
+    }
+
     // `f_min_backup_update(&mut self, pairs: Vec<(usize.usize))` updates and returns actual `f_min`
     pub fn f_min_backup_update(&mut self, pairs: Vec<(usize, usize)>) -> usize {
+        self.f_min_backup_version = self.forbidden_version; // the backup is evaluated below
         let f_min = intersection_size(&pairs, &self.forbidden);
         // compare and update assignments.f_min_backup to the actual f_min_backup
         if self.f_min_backup == None {
@@ -653,6 +776,76 @@ impl Assignments {
         f_min // `f_min` of the new backup
     }
 
+    // `metrics` of the current assignments
+    pub fn metrics(&self) -> Metrics {
+        Metrics::of(&self.get_pairs_of_ids(), &self.forbidden, self.m, self.n)
+    }
+
+    pub fn snapshots(&self) -> &BTreeMap<String, Vec<(usize, usize)>> {
+        &self.snapshots
+    }
+
+    // `snapshot_save` saves the current assignments as `name` and returns true if it replaced an old snapshot
+    pub fn snapshot_save(&mut self, name: &str) -> bool {
+        self.snapshots
+            .insert(name.to_string(), self.get_pairs_of_ids())
+            .is_some()
+    }
+
+    // `snapshot_restore` sets the assignments saved as `name` (the current ones remain if it is not valid)
+    pub fn snapshot_restore(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        let Some(pairs) = self.snapshots.get(name).cloned() else {
+            return Err(format!("There is no snapshot `{name}` !!!").into());
+        };
+        let tmp = self.get_pairs_of_ids();
+        self.set_pairs_of_ids(&pairs);
+        if let Err(err) = self.test_assignments() {
+            self.set_pairs_of_ids(&tmp);
+            return Err(format!("Snapshot `{name}` is not valid: {err}").into());
+        }
+        Ok(())
+    }
+
+    pub fn pool(&self) -> &SolutionPool {
+        &self.pool
+    }
+
+    pub fn pool_set_capacity(&mut self, capacity: usize) {
+        self.pool.set_capacity(capacity);
+    }
+
+    // `pool_offer` offers `pairs` with `f` forbidden pairs to the solution pool
+    pub fn pool_offer(&mut self, pairs: &Vec<(usize, usize)>, f: usize) -> bool {
+        if !self.pool.may_accept(f) {
+            return false;
+        }
+        let metrics = Metrics::of(pairs, &self.forbidden, self.m, self.n);
+        self.pool.offer(pairs, metrics, self.forbidden_version)
+    }
+
+    // `pool_restore` sets the assignments of the `i`-th pool entry (the current ones are offered to the pool)
+    pub fn pool_restore(&mut self, i: usize) -> Result<(), Box<dyn Error>> {
+        let Some(entry) = self.pool.entries().get(i) else {
+            return Err(format!(
+                "There is no pool entry {i} (pool size {}) !!!",
+                self.pool.entries().len()
+            )
+            .into());
+        };
+        let pairs = entry.pairs.clone();
+        let current = self.get_pairs_of_ids();
+        self.pool_offer(&current, self.number_of_forbidden_used());
+        self.set_pairs_of_ids(&pairs);
+        Ok(())
+    }
+
+    // `metrics` of the assignments saved as `name`
+    pub fn snapshot_metrics(&self, name: &str) -> Option<Metrics> {
+        self.snapshots
+            .get(name)
+            .map(|pairs| Metrics::of(pairs, &self.forbidden, self.m, self.n))
+    }
+
     // get pair of assigned IDs for a pair from self.pairs
     pub fn get_pair_of_ids(&self, pair: &Pair) -> (usize, usize) {
         (
@@ -692,7 +885,7 @@ impl Assignments {
         self.pairs.sort_by_right();
     }
 
-    // add_forbidden
+    // add_forbidden (the backup and the pool stay stale until `rescore`)
     pub fn add_forbidden(&mut self, l: usize, r: usize) -> Result<(), Box<dyn Error>> {
         let mut err = String::new();
         if l >= self.m {
@@ -718,10 +911,13 @@ impl Assignments {
         } else {
             self.forbidden.push((l, r));
             self.forbidden.sort();
+            self.forbidden_version += 1;
             Ok(())
         }
     }
 
+    // `extract_forbidden_by` removes the forbidden pairs passing `test` (the backup and the pool
+    // stay stale until `rescore`)
     pub fn extract_forbidden_by<T: Fn((usize, usize)) -> bool>(
         &mut self,
         test: T,
@@ -735,6 +931,9 @@ impl Assignments {
                 i += 1;
             }
         }
+        if !out.is_empty() {
+            self.forbidden_version += 1;
+        }
         out
     }
 
@@ -1,8 +1,10 @@
 // use serde::{Deserialize, Serialize};
 
+use kmn_pairs::cli;
 // use kmn_pairs::cmd::*;
 use kmn_pairs::kmn_serde::*;
 use kmn_pairs::menu::*;
+use kmn_pairs::progress::install_interrupt_handler;
 use kmn_pairs::*;
 
 // use std::io;
@@ -10,6 +12,24 @@ use kmn_pairs::*;
 // use rand::Rng;
 
 fn main() {
+    // Ctrl-C stops the running search (and the program otherwise)
+    if let Err(err) = install_interrupt_handler() {
+        eprintln!("Ctrl-C handler not installed: {err}");
+    }
+    // non-interactive subcommands (see `kmn-pairs help`)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let help = matches!(args.first().map(String::as_str), Some("-h" | "--help"));
+    if !args.is_empty() && (help || !args[0].starts_with("--")) {
+        std::process::exit(cli::run(&args));
+    }
+    let options = match cli::menu_options(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}\n{}", cli::CLI_HELP);
+            std::process::exit(cli::EXIT_INVALID);
+        }
+    };
+
     let mut assignments_data: Option<Assignments> = None;
 
     println!(
@@ -53,11 +73,11 @@ fn main() {
 
     // TEST result
     println!("\nFINAL RESULT:\n");
-    if let Some(assignments) = assignments_data {
+    if let Some(assignments) = &assignments_data {
         println!("Data has been set to: {}", assignments);
         println!(
             "JSON:\n{}",
-            match serde_json::to_string(&SerdeKmnAssignment::from(&assignments)) {
+            match serde_json::to_string(&SerdeKmnAssignment::from(assignments)) {
                 Ok(out) => out,
                 Err(err) => err.to_string(),
             }
@@ -66,4 +86,20 @@ fn main() {
         println!("None assignments data has been set!");
     }
     println!("");
+
+    if let (Some(path), Some(assignments)) = (&options.out, &assignments_data)
+        && let Err(err) = write_assignments_file(assignments, path)
+    {
+        eprintln!("{path}: {err}");
+        std::process::exit(cli::EXIT_INVALID);
+    }
+    if options.script_mode {
+        let code = cli::script_exit_code();
+        match &assignments_data {
+            Some(assignments) if code == cli::EXIT_SOLVED => {
+                std::process::exit(cli::exit_code(assignments))
+            }
+            _ => std::process::exit(code),
+        }
+    }
 }
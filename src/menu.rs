@@ -4,22 +4,151 @@ use self::action::*;
 
 // use serde::{Deserialize, Serialize};
 use crate::cmd::*;
+use crate::history::*;
 use crate::kmn_serde::*;
+use crate::skeleton::*;
 use crate::*;
 
 // use rand::Rng;
 // use std::error::Error;
 // use std::fmt::Write;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::cell::RefCell;
+use std::fmt::Display;
 use std::io;
 // use std::str::FromStr;
 
-// read line of input from io::stdin()
+// `Script` - lines of a command file replacing io::stdin() in `read_line`
+#[derive(Debug)]
+pub struct Script {
+    lines: Vec<(usize, String)>, // (line number in the file, line)
+    next: usize,                 // index of the next line to read
+    keep_going: bool,            // do not stop on errors
+    failed: usize,               // number of errors reported while executing the script
+}
+
+// state of the menus' input
+#[derive(Debug, Default)]
+struct Input {
+    script: Option<Script>,
+    closed: bool,      // end of input (or the script stopped on error)
+    seed: Option<u64>, // seed of the menus' random number generators
+}
+
+thread_local! {
+    static INPUT: RefCell<Input> = RefCell::new(Input::default());
+}
+
+// `set_script` - the following `read_line` calls read (and echo) the lines of `contents`
+// skipping empty lines and comments (lines starting with `#`); keystroke files starting with
+// the `k m n` answer (e.g. `test-data/test-data-3-10-19-t1`) get the implied `kmn` command
+pub fn set_script(contents: &str, keep_going: bool) {
+    let mut lines: Vec<(usize, String)> = contents
+        .lines()
+        .enumerate()
+        .map(|(i, x)| (i + 1, x.trim().to_string()))
+        .filter(|(_, x)| !x.is_empty() && !x.starts_with('#'))
+        .collect();
+    if let Some((number, first)) = lines.first() {
+        let words: Vec<&str> = first.split_whitespace().collect();
+        if words.len() == 3 && words.iter().all(|x| x.parse::<usize>().is_ok()) {
+            lines.insert(0, (*number, "kmn".to_string()));
+        }
+    }
+    INPUT.with_borrow_mut(|input| {
+        input.script = Some(Script {
+            lines,
+            next: 0,
+            keep_going,
+            failed: 0,
+        });
+        input.closed = false;
+    });
+}
+
+// `set_seed` - the menus' random number generators start from `seed` (for reproducible sessions)
+pub fn set_seed(seed: Option<u64>) {
+    INPUT.with_borrow_mut(|input| input.seed = seed);
+}
+
+// random number generator for a menu (seeded by `set_seed` if set)
+pub fn menu_rng() -> StdRng {
+    match INPUT.with_borrow(|input| input.seed) {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    }
+}
+
+// number of errors reported while executing the script (`None` in interactive mode)
+pub fn script_errors() -> Option<usize> {
+    INPUT.with_borrow(|input| input.script.as_ref().map(|script| script.failed))
+}
+
+// `input_closed` is true after `read_line` has reached the end of input
+pub fn input_closed() -> bool {
+    INPUT.with_borrow(|input| input.closed)
+}
+
+// `print_error` prints `err`; in script mode without `keep_going` it also stops the script
+pub fn print_error<T: Display>(err: T) {
+    println!("{err}");
+    INPUT.with_borrow_mut(|input| {
+        if let Some(script) = &mut input.script {
+            script.failed += 1;
+            if !script.keep_going {
+                let number = match script.next {
+                    0 => 0,
+                    next => script.lines[next - 1].0,
+                };
+                println!("#ERROR# Script stopped at line {number} !!!");
+                input.closed = true;
+            }
+        }
+    });
+}
+
+// read line of input from io::stdin() (or from the script)
+// returns "" after the end of input
 pub fn read_line() -> String {
-    let mut input = String::new();
-    io::stdin()
-        .read_line(&mut input)
-        .expect("Could not read line from input)");
-    input
+    INPUT.with_borrow_mut(|input| {
+        if input.closed {
+            return String::new();
+        }
+        if let Some(script) = &mut input.script {
+            if let Some((_, line)) = script.lines.get(script.next) {
+                script.next += 1;
+                println!("{line}"); // echo
+                return format!("{line}\n");
+            }
+            input.closed = true;
+            return String::new();
+        }
+        let mut line = String::new();
+        let bytes = io::stdin()
+            .read_line(&mut line)
+            .expect("Could not read line from input)");
+        if bytes == 0 {
+            input.closed = true;
+        }
+        line
+    })
+}
+
+// `split_skeleton` splits the optional skeleton kind after `number` arguments from `input`
+// (`SkeletonKind::Cyclic` if missing)
+fn split_skeleton(input: String, number: usize) -> Option<(String, SkeletonKind)> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    if words.len() != number + 1 {
+        return Some((input, SkeletonKind::Cyclic));
+    }
+    match words[number].parse() {
+        Ok(skeleton) => Some((words[..number].join(" "), skeleton)),
+        Err(err) => {
+            print_error(err);
+            None
+        }
+    }
 }
 
 fn input_menu(assignments_data: &mut Option<Assignments>) {
@@ -27,6 +156,10 @@ fn input_menu(assignments_data: &mut Option<Assignments>) {
         println!("\nDEFINE ASSIGNMENTS");
         println!("-> Input command (h for help): ");
         let cmd = read_line();
+        if input_closed() {
+            println!("End of input.");
+            break 'input;
+        }
         let cmd = cmd.trim();
         match cmd {
             "h" => {
@@ -44,34 +177,52 @@ fn input_menu(assignments_data: &mut Option<Assignments>) {
                 break 'input;
             }
             "kmn" => {
-                println!("{}: input: k m n (1 <= k <= m <= n)", cmd);
-                let input = read_line();
+                println!(
+                    "{}: input: k m n [skeleton] (1 <= k <= m <= n, skeleton: {})",
+                    cmd,
+                    SkeletonKind::names()
+                );
+                let Some((input, skeleton)) = split_skeleton(read_line(), 3) else {
+                    continue 'input;
+                };
                 match split_and_parse_input::<usize>(&input, 3) {
                     Ok(args) => {
                         let (k, m, n) = (args[0], args[1], args[2]);
                         if !(1 <= k && k <= m && m <= n) {
-                            println!(
+                            print_error(format!(
                                 "You have input (k,m,n)={:?}, that does not meet the condition: 1 <= k <= m <= n !!!",
                                 (k, m, n)
-                            );
+                            ));
                             continue 'input;
                         } else {
                             // Ok,  set the assignments !
-                            let assignments = Assignments::new(k, m, n);
+                            let assignments =
+                                Assignments::with_skeleton(skeleton, k, m, n, &mut menu_rng());
+                            if assignments.skeleton() != skeleton {
+                                println!(
+                                    "#WARNING# No {skeleton} skeleton found, the cyclic one used."
+                                );
+                            }
                             *assignments_data = Some(assignments);
                             println!("Default assignments for (k, m, n)=({},{},{}) set!", k, m, n);
                             break 'input; // go to the loop of commands
                         }
                     }
                     Err(err) => {
-                        println!("{}", err);
+                        print_error(err);
                         continue 'input; // try again!
                     }
                 }
             }
             "mn" => {
-                println!("{}: input: m n and then either k or p", cmd);
-                let input = read_line();
+                println!(
+                    "{}: input: m n [skeleton] and then either k or p (skeleton: {})",
+                    cmd,
+                    SkeletonKind::names()
+                );
+                let Some((input, skeleton)) = split_skeleton(read_line(), 2) else {
+                    continue 'input;
+                };
                 match split_and_parse_input::<usize>(&input, 2) {
                     Ok(args) => {
                         let (m, n) = (args[0], args[1]);
@@ -83,19 +234,19 @@ fn input_menu(assignments_data: &mut Option<Assignments>) {
 
                         match split_and_parse_input::<usize>(&read_line(), 1) {
                             Err(err) => {
-                                println!("{err}");
+                                print_error(err);
                                 continue 'input;
                             }
                             Ok(k_or_p) => {
                                 let k_or_p = k_or_p[0];
                                 if k_or_p < 1 {
-                                    println!("You have input {k_or_p} < 1 !!!");
+                                    print_error(format!("You have input {k_or_p} < 1 !!!"));
                                     continue 'input;
                                 } else if m <= n && m < k_or_p {
-                                    println!("You have input {k_or_p} > m !!!");
+                                    print_error(format!("You have input {k_or_p} > m !!!"));
                                     continue 'input;
                                 } else if n < m && n < k_or_p {
-                                    println!("You have input {k_or_p} > n !!!");
+                                    print_error(format!("You have input {k_or_p} > n !!!"));
                                     continue 'input;
                                 } else {
                                     // k_or_p is correct either k or p
@@ -106,7 +257,22 @@ fn input_menu(assignments_data: &mut Option<Assignments>) {
                                         (k, p) = (None, Some(k_or_p));
                                     }
                                     // let _pairs = Pairs::kmnp_pairs(k, m, n, p);
-                                    let assignments = Assignments::new_kmnp(k, m, n, p);
+                                    let mut assignments = Assignments::new_kmnp(k, m, n, p);
+                                    if !skeleton.is_cyclic() {
+                                        let (k, m, n) = assignments.get_kmn();
+                                        assignments = Assignments::with_skeleton(
+                                            skeleton,
+                                            k,
+                                            m,
+                                            n,
+                                            &mut menu_rng(),
+                                        );
+                                        if assignments.skeleton() != skeleton {
+                                            println!(
+                                                "#WARNING# No {skeleton} skeleton found, the cyclic one used."
+                                            );
+                                        }
+                                    }
                                     *assignments_data = Some(assignments);
                                     println!(
                                         "Default assignments for (k, m, n, p)=({k:?},{m},{n},{p:?}) set!"
@@ -117,7 +283,7 @@ fn input_menu(assignments_data: &mut Option<Assignments>) {
                         }
                     }
                     Err(err) => {
-                        println!("{}", err);
+                        print_error(err);
                         continue 'input; // try again!
                     }
                 }
@@ -140,29 +306,31 @@ fn input_menu(assignments_data: &mut Option<Assignments>) {
                                 break 'input;
                             }
                             Err(err) => {
-                                println!("{}", err);
+                                print_error(err);
                                 continue 'input; // try again!
                             }
                         }
                     }
                     Err(err) => {
-                        println!("{}", err);
+                        print_error(err);
                         continue 'input; // try again!
                     }
                 }
             }
-            _ => println!("Unknown command: {}", cmd),
+            _ => print_error(format!("Unknown command: {}", cmd)),
         }
     }
 }
 
 // `kmn_pairs_menu` - define and update Assignments
+#[allow(clippy::never_loop)] // the outer loop only labels `break 'cmd`
 pub fn kmn_pairs_menu(assignments_data: &mut Option<Assignments>) {
-    let mut rng = rand::rng(); // random number generator
+    let mut rng = menu_rng(); // random number generator
     if assignments_data.is_none() {
         input_menu(assignments_data);
     }
     if let Some(assignments) = assignments_data {
+        let mut history = History::new(&*assignments, HISTORY_MAX);
         'cmd: loop {
             // MENU - Actions on the assignments:
             loop {
@@ -175,7 +343,17 @@ pub fn kmn_pairs_menu(assignments_data: &mut Option<Assignments>) {
                 );
                 println!("-> Input command (h for help): ");
                 let cmd = read_line();
-                let cmd = cmd.trim();
+                if input_closed() {
+                    println!("End of input.");
+                    break 'cmd;
+                }
+                let line = cmd.trim();
+                // command with optional inline argument (e.g. `snap best`)
+                let (cmd, arg) = match line.split_once(char::is_whitespace) {
+                    Some((cmd, arg)) => (cmd, arg.trim()),
+                    None => (line, ""),
+                };
+                let forbidden_version = assignments.forbidden_version();
                 match cmd {
                     "h" => {
                         println!(
@@ -200,8 +378,38 @@ pub fn kmn_pairs_menu(assignments_data: &mut Option<Assignments>) {
             slr      swap left and right IDs of forbidden with random other left and right IDs
             bsr      back and swap right IDs of forbidden with random other right IDs
             bslr     back and swap left and right IDs of forbidden with random other left and right IDs
+                     (rl ... bslr accept a time budget, e.g. `rlr --time 30s`; Ctrl-C stops the search)
             sbrk     'skeleton breaking' (result may be not isomorphic)
-            back     go back to the backup with minimal forbidden pairs in assignments
+            search   search with a registered strategy (`search <strategy> name=value ...`, `search` lists them)
+            sample   uniformly random valid assignments (any structure, without forbidden pairs) by a Markov chain
+                     of switches and shifts (`sample [steps=N]`, 100 steps per pair by default)
+            cover    covering mode: assignments where as many pairs of rights as possible share a left, by annealing
+                     over switches and shifts avoiding forbidden pairs (`cover [steps=N]`, 1000 steps per pair by default)
+            exact    exact solver for small instances: sets assignments with the minimal number of forbidden pairs
+                     (`exact [count] [nodes=N]`, `count` also counts the optimal assignments)
+            cp       constraint programming solver: assignments without forbidden pairs (`cp [name=value ...]`,
+                     nodes=N, time=DURATION, minimize=overlap|none, side constraints max_overlap=N,
+                     conflict=L1-L2, group=L1,L2,...:MIN:MAX; prints status optimal/feasible/unknown/infeasible)
+            trace    `trace on|off` trace the following searches, `trace` summary and chart of the last one,
+                     `trace json|csv [FILE]` print (or write) the last trace
+            repair   minimal-disruption repair: assignments with the fewest forbidden pairs changing the fewest
+                     current pairs (prints the changes and the lefts affected)
+            rml      remove lefts keeping as many pairs as possible (`rml <ids> [k=K]`, ids of the following lefts
+                     decrease; the snapshots and the pool are dropped)
+            rmr      remove rights keeping as many pairs as possible (`rmr <ids> [k=K]`)
+            addl     add new lefts with the next ids keeping as many pairs as possible (`addl [count] [k=K]`)
+            addr     add new rights with the next ids keeping as many pairs as possible (`addr [count] [k=K]`)
+            merge    merge assignments of a JSON file into current (`merge <file> [name=value ...]`, lefts=MAPPING and
+                     rights=MAPPING map the ids of the file: same, disjoint or ID:MERGED_ID,..., k=K, prefer=first|second
+                     resolves conflicting pairs; forbidden pairs united, merged pairs repaired and tested)
+            split    split into tracks solved separately (`split <N>|<tracks>|<file> [k=K]`, N tracks clustering
+                     the allowed pairs, `L,L,.../R,R,...;...` lefts/rights of each track or a JSON file of tracks;
+                     prints the tracks and their combined pairs, current assignments are kept)
+            dot      write Graphviz DOT of assignments (`dot <path>`, forbidden pairs red dashed, forbidden in assignments
+                     red and thick)
+            svg      write SVG picture of assignments drawn without Graphviz (`svg <path>`, the same styles as `dot`)
+            back     go back to the backup with minimal forbidden pairs in assignments (if not worse than current)
+            BACK!    go back to the backup even if it is worse than current assignments
             af       add forbidden pairs
             arf      try to add some random forbidden pairs
             arfl     try to add some random forbidden pairs with given left id
@@ -211,6 +419,20 @@ pub fn kmn_pairs_menu(assignments_data: &mut Option<Assignments>) {
             dfl      delete all forbidden pairs with left ID l (prints deleted pairs)
             dfr      delete all forbidden pairs with right ID r (prints deleted pairs)
             test     do some tests ...
+            snap     save current assignments as named snapshot (`snap <name>`)
+            snaps    list snapshots with forbidden counts and overlap metrics
+            restore  restore named snapshot (`restore <name>`)
+            diffsnap print differences of two snapshots (`diffsnap <a> <b>`)
+            diff     print changes from assignments of a JSON file to current ones by lefts and by rights (`diff <file>`)
+            iso      canonical hash of current assignments (the same for isomorphic ones), `iso <snapshot>|<file>`
+                     also tells if they are isomorphic to current (`fixed=lefts|rights|both` keeps the labels of sides)
+            pool     list the pool of best distinct assignments found by searches
+            poolp    print assignments of pool entry (`poolp <index>`)
+            poolr    restore assignments of pool entry (`poolr <index>`)
+            poolcap  set the capacity of the pool (`poolcap <capacity>`)
+            undo     undo the last command changing the assignments
+            redo     redo the undone command
+            history  list the steps of undo/redo history
             quit     quit the 'EDIT ASSIGNMENTS' menu (prints JSON assignments data)
 "
                         );
@@ -253,31 +475,52 @@ pub fn kmn_pairs_menu(assignments_data: &mut Option<Assignments>) {
                     }
 
                     "rl" => {
-                        rl(assignments, &mut rng);
+                        rl(assignments, arg, &mut rng);
                     }
                     "rr" => {
-                        rr(assignments, &mut rng);
+                        rr(assignments, arg, &mut rng);
                     }
                     "rlr" => {
-                        rlr(assignments, &mut rng);
+                        rlr(assignments, arg, &mut rng);
                     }
                     "sl" => {
-                        sl(assignments, &mut rng);
+                        sl(assignments, arg, &mut rng);
                     }
                     "sr" => {
-                        sr(assignments, &mut rng);
+                        sr(assignments, arg, &mut rng);
                     }
                     "slr" => {
-                        slr(assignments, &mut rng);
+                        slr(assignments, arg, &mut rng);
                     }
                     "back" => {
-                        back(assignments);
+                        back(assignments, false);
+                    }
+                    "BACK!" => {
+                        back(assignments, true);
                     }
                     "bsr" => {
-                        bsr(assignments, &mut rng);
+                        bsr(assignments, arg, &mut rng);
                     }
                     "bslr" => {
-                        bslr(assignments, &mut rng);
+                        bslr(assignments, arg, &mut rng);
+                    }
+                    "search" => {
+                        search(assignments, arg, &mut rng);
+                    }
+                    "trace" => {
+                        trace(arg);
+                    }
+                    "exact" => {
+                        exact(assignments, arg);
+                    }
+                    "sample" => {
+                        sample(assignments, arg, &mut rng);
+                    }
+                    "cover" => {
+                        cover(assignments, arg, &mut rng);
+                    }
+                    "cp" => {
+                        cp(assignments, arg);
                     }
                     "sbrk" => {
                         sbrk(assignments);
@@ -329,7 +572,7 @@ pub fn kmn_pairs_menu(assignments_data: &mut Option<Assignments>) {
                     "test" => {
                         println!("test_assignments ...");
                         if let Err(err) = assignments.test_assignments() {
-                            println!("{}", err);
+                            print_error(err);
                         } else {
                             println!("Implemented tests passed.");
                         }
@@ -340,6 +583,68 @@ pub fn kmn_pairs_menu(assignments_data: &mut Option<Assignments>) {
                             println!("Implemented tests passed.");
                         }
                     }
+                    "snap" => {
+                        snap(assignments, arg);
+                    }
+                    "snaps" => {
+                        snaps(assignments);
+                    }
+                    "restore" => {
+                        restore(assignments, arg);
+                    }
+                    "diffsnap" => {
+                        diffsnap(assignments, arg);
+                    }
+                    "rml" | "rmr" | "addl" | "addr" => {
+                        resize(assignments, cmd, arg);
+                    }
+                    "merge" => {
+                        merge(assignments, arg);
+                    }
+                    "split" => {
+                        split(assignments, arg);
+                    }
+                    "dot" | "svg" => {
+                        picture(assignments, cmd, arg);
+                    }
+                    "repair" => {
+                        repair(assignments);
+                    }
+                    "diff" => {
+                        diff(assignments, arg);
+                    }
+                    "iso" => {
+                        iso(assignments, arg);
+                    }
+                    "pool" => {
+                        pool(assignments);
+                    }
+                    "poolp" => {
+                        poolp(assignments, arg);
+                    }
+                    "poolr" => {
+                        poolr(assignments, arg);
+                    }
+                    "poolcap" => {
+                        poolcap(assignments, arg);
+                    }
+                    "undo" => {
+                        undo(assignments, &mut history);
+                    }
+                    "redo" => {
+                        redo(assignments, &mut history);
+                    }
+                    "history" => {
+                        println!(
+                            "{cmd}: (step, command, forbidden in assignments / forbidden, backup)\n{}",
+                            history.lines(|a| format!(
+                                "({} / {}, {})",
+                                a.number_of_forbidden_used(),
+                                a.forbidden().len(),
+                                a.backup_header()
+                            ))
+                        );
+                    }
                     "quit" => {
                         println!(
                             "ASSIGNMENTS JSON:\n{}\n",
@@ -352,8 +657,15 @@ pub fn kmn_pairs_menu(assignments_data: &mut Option<Assignments>) {
                         break 'cmd;
                         // return;
                     }
-                    _ => println!("Unknown command: {}", cmd),
+                    _ => print_error(format!("Unknown command: {}", cmd)),
+                }
+                // the backup and the pool were evaluated against the old forbidden pairs
+                if assignments.forbidden_version() != forbidden_version
+                    && !matches!(cmd, "undo" | "redo")
+                {
+                    rescore(assignments);
                 }
+                history.record(line, assignments);
             }
         }
     } else {
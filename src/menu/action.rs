@@ -1,9 +1,34 @@
 // action of kmn_pairs menu
 
 use crate::cmd::*;
+use crate::cover::*;
+use crate::cp::*;
+use crate::diff::*;
+use crate::exact::*;
+use crate::history::*;
+use crate::iso::*;
 // use crate::kmn_serde::*;
 use crate::menu::*;
+use crate::merge::*;
+use crate::progress::*;
+use crate::sample::*;
+use crate::split::*;
+use crate::strategy::*;
+use crate::trace::*;
 use crate::*;
+use std::cell::RefCell;
+use std::time::Duration;
+
+// state of the `trace` command
+#[derive(Debug, Default)]
+struct Tracing {
+    enabled: bool,
+    last: Option<Trace>, // trace of the last search
+}
+
+thread_local! {
+    static TRACING: RefCell<Tracing> = RefCell::new(Tracing::default());
+}
 
 // kmn_pairs_menu actions
 
@@ -51,164 +76,219 @@ pub fn palvrvj(assignments: &Assignments) {
     lvrvj_r(assignments, pairs);
 }
 
-pub fn rl(assignments: &mut Assignments, rng: &mut impl Rng) {
+pub fn rl(assignments: &mut Assignments, arg: &str, rng: &mut impl Rng) {
     let cmd = "rl";
-    println!(
-        "{}: input: max (0 <= max) for max trials to find assignments without forbidden: ",
-        cmd
-    );
-    let input = read_line();
-    match split_and_parse_input::<usize>(&input, 1) {
+    let Some(time) = search_time(cmd, arg) else {
+        return;
+    };
+    match search_input(cmd, time, false) {
         Ok(args) => {
             let max = args[0];
-            println!("max = {}", max);
-            let (Steps(l_steps), Steps(r_steps), Forbidden(f)) =
-                assignments.randomize_permutation(Side::Left, max, rng);
-            println!(
-                "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
-                cmd, l_steps, r_steps, f
-            );
+            if time.is_none() {
+                println!("max = {}", max);
+            }
+            match Permute::search_with_progress(
+                assignments,
+                Side::Left,
+                Budget { max, time },
+                rng,
+                &mut menu_progress(cmd),
+            ) {
+                Ok((Steps(l_steps), Steps(r_steps), Forbidden(f))) => println!(
+                    "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
+                    cmd, l_steps, r_steps, f
+                ),
+                Err(err) => print_error(format!("{cmd}: {err}")),
+            }
         }
         Err(err) => {
-            println!("{cmd}: {}", err);
+            print_error(format!("{cmd}: {}", err));
             // continue 'cmd; // try again!
         }
     }
 }
 
-pub fn rr(assignments: &mut Assignments, rng: &mut impl Rng) {
+pub fn rr(assignments: &mut Assignments, arg: &str, rng: &mut impl Rng) {
     let cmd = "rr";
-    println!(
-        "{}: input: max (0 <= max) for max trials to find assignments without forbidden: ",
-        cmd
-    );
-    let input = read_line();
-    match split_and_parse_input::<usize>(&input, 1) {
+    let Some(time) = search_time(cmd, arg) else {
+        return;
+    };
+    match search_input(cmd, time, false) {
         Ok(args) => {
             let max = args[0];
-            println!("max = {}", max);
-            let (Steps(l_steps), Steps(r_steps), Forbidden(f)) =
-                assignments.randomize_permutation(Side::Right, max, rng);
-            println!(
-                "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
-                cmd, l_steps, r_steps, f
-            );
+            if time.is_none() {
+                println!("max = {}", max);
+            }
+            match Permute::search_with_progress(
+                assignments,
+                Side::Right,
+                Budget { max, time },
+                rng,
+                &mut menu_progress(cmd),
+            ) {
+                Ok((Steps(l_steps), Steps(r_steps), Forbidden(f))) => println!(
+                    "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
+                    cmd, l_steps, r_steps, f
+                ),
+                Err(err) => print_error(format!("{cmd}: {err}")),
+            }
         }
         Err(err) => {
-            println!("{cmd}: {}", err);
+            print_error(format!("{cmd}: {}", err));
             // continue 'cmd; // try again!
         }
     }
 }
 
-pub fn rlr(assignments: &mut Assignments, rng: &mut impl Rng) {
+pub fn rlr(assignments: &mut Assignments, arg: &str, rng: &mut impl Rng) {
     let cmd = "rlr";
-    println!(
-        "{}: input: max l_percent (0 <= max and 0<= l_percent <= 100): ",
-        cmd
-    );
-    let input = read_line();
-    match split_and_parse_input::<usize>(&input, 2) {
+    let Some(time) = search_time(cmd, arg) else {
+        return;
+    };
+    match search_input(cmd, time, true) {
         Ok(args) => {
             let (max, l_percent) = (args[0], args[1]);
-            println!("max = {}, l_percent = {}", max, l_percent);
-            let (Steps(l_steps), Steps(r_steps), Forbidden(f)) =
-                assignments.randomize_permutation(Side::LeftPercent(l_percent), max, rng);
-            println!(
-                "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
-                cmd, l_steps, r_steps, f
-            );
+            match time {
+                Some(_) => println!("l_percent = {}", l_percent),
+                None => println!("max = {}, l_percent = {}", max, l_percent),
+            }
+            match Permute::search_with_progress(
+                assignments,
+                Side::LeftPercent(l_percent),
+                Budget { max, time },
+                rng,
+                &mut menu_progress(cmd),
+            ) {
+                Ok((Steps(l_steps), Steps(r_steps), Forbidden(f))) => println!(
+                    "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
+                    cmd, l_steps, r_steps, f
+                ),
+                Err(err) => print_error(format!("{cmd}: {err}")),
+            }
         }
         Err(err) => {
-            println!("{cmd}: {}", err);
+            print_error(format!("{cmd}: {}", err));
             // continue 'cmd; // try again!
         }
     }
 }
 
-pub fn sl(assignments: &mut Assignments, rng: &mut impl Rng) {
+pub fn sl(assignments: &mut Assignments, arg: &str, rng: &mut impl Rng) {
     let cmd = "sl";
-    println!(
-        "{}: input: max (0 <= max) for max trials to find assignments without forbidden: ",
-        cmd
-    );
-    let input = read_line();
-    match split_and_parse_input::<usize>(&input, 1) {
+    let Some(time) = search_time(cmd, arg) else {
+        return;
+    };
+    match search_input(cmd, time, false) {
         Ok(args) => {
             let max = args[0];
-            println!("max = {}", max);
-            let (Steps(l_steps), Steps(r_steps), Forbidden(f)) =
-                assignments.random_swaps(Side::Left, max, rng);
-            println!(
-                "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
-                cmd, l_steps, r_steps, f
-            );
+            if time.is_none() {
+                println!("max = {}", max);
+            }
+            match Swap::search_with_progress(
+                assignments,
+                Side::Left,
+                Budget { max, time },
+                rng,
+                &mut menu_progress(cmd),
+            ) {
+                Ok((Steps(l_steps), Steps(r_steps), Forbidden(f))) => println!(
+                    "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
+                    cmd, l_steps, r_steps, f
+                ),
+                Err(err) => print_error(format!("{cmd}: {err}")),
+            }
         }
         Err(err) => {
-            println!("{cmd}: {}", err);
+            print_error(format!("{cmd}: {}", err));
             // continue 'cmd; // try again!
         }
     }
 }
 
-pub fn sr(assignments: &mut Assignments, rng: &mut impl Rng) {
+pub fn sr(assignments: &mut Assignments, arg: &str, rng: &mut impl Rng) {
     let cmd = "sr";
-    println!(
-        "{}: input: max (0 <= max) for max trials to find assignments without forbidden: ",
-        cmd
-    );
-    let input = read_line();
-    match split_and_parse_input::<usize>(&input, 1) {
+    let Some(time) = search_time(cmd, arg) else {
+        return;
+    };
+    match search_input(cmd, time, false) {
         Ok(args) => {
             let max = args[0];
-            println!("max = {}", max);
-            let (Steps(l_steps), Steps(r_steps), Forbidden(f)) =
-                assignments.random_swaps(Side::Right, max, rng);
-            println!(
-                "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
-                cmd, l_steps, r_steps, f
-            );
+            if time.is_none() {
+                println!("max = {}", max);
+            }
+            match Swap::search_with_progress(
+                assignments,
+                Side::Right,
+                Budget { max, time },
+                rng,
+                &mut menu_progress(cmd),
+            ) {
+                Ok((Steps(l_steps), Steps(r_steps), Forbidden(f))) => println!(
+                    "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
+                    cmd, l_steps, r_steps, f
+                ),
+                Err(err) => print_error(format!("{cmd}: {err}")),
+            }
         }
         Err(err) => {
-            println!("{cmd}: {}", err);
+            print_error(format!("{cmd}: {}", err));
             // continue 'cmd; // try again!
         }
     }
 }
 
-pub fn slr(assignments: &mut Assignments, rng: &mut impl Rng) {
+pub fn slr(assignments: &mut Assignments, arg: &str, rng: &mut impl Rng) {
     let cmd = "slr";
-    println!(
-        "{}: input: max l_percent (0 <= max and 0<= l_percent <= 100): ",
-        cmd
-    );
-    let input = read_line();
-    match split_and_parse_input::<usize>(&input, 2) {
+    let Some(time) = search_time(cmd, arg) else {
+        return;
+    };
+    match search_input(cmd, time, true) {
         Ok(args) => {
             let (max, l_percent) = (args[0], args[1]);
-            println!("max = {}, l_percent = {}", max, l_percent);
-            let (Steps(l_steps), Steps(r_steps), Forbidden(f)) =
-                assignments.random_swaps(Side::LeftPercent(l_percent), max, rng);
-            println!(
-                "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
-                cmd, l_steps, r_steps, f
-            );
+            match time {
+                Some(_) => println!("l_percent = {}", l_percent),
+                None => println!("max = {}, l_percent = {}", max, l_percent),
+            }
+            match Swap::search_with_progress(
+                assignments,
+                Side::LeftPercent(l_percent),
+                Budget { max, time },
+                rng,
+                &mut menu_progress(cmd),
+            ) {
+                Ok((Steps(l_steps), Steps(r_steps), Forbidden(f))) => println!(
+                    "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
+                    cmd, l_steps, r_steps, f
+                ),
+                Err(err) => print_error(format!("{cmd}: {err}")),
+            }
         }
         Err(err) => {
-            println!("{cmd}: {}", err);
+            print_error(format!("{cmd}: {}", err));
             // continue 'cmd; // try again!
         }
     }
 }
 
-pub fn back(assignments: &mut Assignments) {
-    let cmd = "back";
+// `back` restores the backup; unless `force` it refuses a backup worse than the current assignments
+pub fn back(assignments: &mut Assignments, force: bool) {
+    let cmd = if force { "BACK!" } else { "back" };
+    if assignments.backup_is_stale() {
+        rescore(assignments);
+    }
     let backup = assignments.f_min_backup.clone();
     if let Some(pairs) = backup {
         let tmp = assignments.get_pairs_of_ids();
-        assignments.set_pairs_of_ids(&pairs);
         let old_f = intersection_size(&tmp, &assignments.forbidden);
-        if old_f < intersection_size(&pairs, &assignments.forbidden) {
+        let backup_f = intersection_size(&pairs, &assignments.forbidden);
+        if old_f < backup_f && !force {
+            print_error(format!(
+                "{cmd}: The {backup_f}-forbidden backup is worse than the current {old_f}-forbidden assignments (use `BACK!` to restore it anyway) !!!"
+            ));
+            return;
+        }
+        assignments.set_pairs_of_ids(&pairs);
+        if old_f < backup_f {
             println!("saving {}-backup from current assignments", old_f);
             assignments.f_min_backup = Some(tmp);
         }
@@ -218,56 +298,824 @@ pub fn back(assignments: &mut Assignments) {
     }
 }
 
-pub fn bsr(assignments: &mut Assignments, rng: &mut impl Rng) {
-    let cmd = "bsr";
+// `rescore` re-evaluates the backup and the pool after the change of forbidden pairs
+pub fn rescore(assignments: &mut Assignments) {
+    let changed = assignments.rescore();
     println!(
-        "{}: input: max (0 <= max) for max trials to find assignments without forbidden: ",
-        cmd
+        "rescore: Forbidden changed: {}, {} of {} pool entries changed the number of forbidden.",
+        assignments.backup_header(),
+        changed,
+        assignments.pool().entries().len()
     );
+}
+
+pub fn undo(assignments: &mut Assignments, history: &mut History<Assignments>) {
+    let cmd = "undo";
+    let undone = history.current_cmd().to_string();
+    match history.undo() {
+        Some(state) => {
+            *assignments = state;
+            println!("{cmd}: `{undone}` undone.");
+        }
+        None => print_error(format!("{cmd}: Nothing to undo !!!")),
+    }
+}
+
+pub fn redo(assignments: &mut Assignments, history: &mut History<Assignments>) {
+    let cmd = "redo";
+    match history.redo() {
+        Some(state) => {
+            *assignments = state;
+            println!("{cmd}: `{}` redone.", history.current_cmd());
+        }
+        None => print_error(format!("{cmd}: Nothing to redo !!!")),
+    }
+}
+
+// `search_time` returns the time budget of a search from inline `arg` (`--time 30s`)
+fn search_time(cmd: &str, arg: &str) -> Option<Option<Duration>> {
+    match arg.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [] => Some(None),
+        ["--time", time] => match parse_duration(time) {
+            Ok(time) => Some(Some(time)),
+            Err(err) => {
+                print_error(format!("{cmd}: {err}"));
+                None
+            }
+        },
+        _ => {
+            print_error(format!(
+                "{cmd}: Unexpected arguments `{arg}` (expected: `--time DURATION`, e.g. `{cmd} --time 30s`) !!!"
+            ));
+            None
+        }
+    }
+}
+
+// `search_input` reads `max` (and `l_percent` if `with_l_percent`) of a search; with a time budget
+// `max` is not asked for (the search runs until the time is over)
+fn search_input(
+    cmd: &str,
+    time: Option<Duration>,
+    with_l_percent: bool,
+) -> Result<Vec<usize>, Box<dyn Error>> {
+    match (time.is_some(), with_l_percent) {
+        (true, false) => return Ok(vec![usize::MAX]),
+        (true, true) => println!("{cmd}: input: l_percent (0<= l_percent <= 100): "),
+        (false, false) => println!(
+            "{cmd}: input: max (0 <= max) for max trials to find assignments without forbidden: "
+        ),
+        (false, true) => {
+            println!("{cmd}: input: max l_percent (0 <= max and 0<= l_percent <= 100): ")
+        }
+    }
     let input = read_line();
+    match time {
+        Some(_) => {
+            let args = split_and_parse_input::<usize>(&input, 1)?;
+            Ok(vec![usize::MAX, args[0]])
+        }
+        None => split_and_parse_input::<usize>(&input, with_l_percent as usize + 1),
+    }
+}
+
+// MenuProgress - prints progress of the search to stdout and keeps its trace (after `trace on`)
+struct MenuProgress(Traced<PrintProgress>);
+
+impl SearchProgress for MenuProgress {
+    fn on_start(&mut self, progress: &Progress) {
+        self.0.on_start(progress);
+    }
+
+    fn on_step(&mut self, progress: &Progress, movement: &str) {
+        self.0.on_step(progress, movement);
+    }
+
+    fn on_report(&mut self, progress: &Progress) {
+        self.0.on_report(progress);
+    }
+
+    fn on_stop(&mut self, progress: &Progress, stop: Stop) {
+        self.0.on_stop(progress, stop);
+        if let Some(trace) = self.0.trace.take() {
+            TRACING.with_borrow_mut(|tracing| tracing.last = Some(trace));
+        }
+    }
+}
+
+// `menu_progress` prints progress of the search `cmd` to stdout
+fn menu_progress(cmd: &str) -> MenuProgress {
+    let enabled = TRACING.with_borrow(|tracing| tracing.enabled);
+    MenuProgress(Traced {
+        inner: PrintProgress {
+            cmd: cmd.to_string(),
+            to_stderr: false,
+        },
+        trace: enabled.then(|| Trace::new(cmd)),
+    })
+}
+
+// `trace` - `trace on|off` (tracing of the following searches), `trace` (summary and chart
+// of the last traced search), `trace json|csv [FILE]` (print or write the last trace)
+pub fn trace(arg: &str) {
+    let cmd = "trace";
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    match words.as_slice() {
+        ["on"] | ["off"] => {
+            let enabled = words[0] == "on";
+            TRACING.with_borrow_mut(|tracing| tracing.enabled = enabled);
+            println!("{cmd}: Tracing of searches {}.", words[0]);
+            return;
+        }
+        [] | ["json" | "csv"] | ["json" | "csv", _] => {}
+        _ => {
+            print_error(format!(
+                "{cmd}: Unexpected arguments `{arg}` (expected: `on`, `off`, `json [FILE]` or `csv [FILE]`) !!!"
+            ));
+            return;
+        }
+    }
+    let Some(trace) = TRACING.with_borrow(|tracing| tracing.last.clone()) else {
+        print_error(format!(
+            "{cmd}: No traced search (`trace on` traces the following searches) !!!"
+        ));
+        return;
+    };
+    match words.as_slice() {
+        [] => {
+            print!("{}", trace.summary());
+            print!("{}", trace.chart(60, 10));
+        }
+        [format] => {
+            if *format == "json" {
+                match trace.to_json() {
+                    Ok(json) => println!("{json}"),
+                    Err(err) => print_error(format!("{cmd}: {err}")),
+                }
+            } else {
+                print!("{}", trace.to_csv());
+            }
+        }
+        [format, path] => {
+            let result = if *format == "json" {
+                trace
+                    .to_json()
+                    .and_then(|json| Ok(std::fs::write(path, json + "\n")?))
+            } else {
+                std::fs::write(path, trace.to_csv()).map_err(|err| err.into())
+            };
+            match result {
+                Ok(()) => println!("{cmd}: Trace written to {path}."),
+                Err(err) => print_error(format!("{cmd}: {err}")),
+            }
+        }
+        _ => unreachable!("checked above"),
+    }
+}
+
+// `search` runs `arg` = `<strategy> name=value ...` (see `strategy::search_by_name`)
+pub fn search(assignments: &mut Assignments, arg: &str, rng: &mut impl Rng) {
+    let cmd = "search";
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    let Some((name, args)) = words.split_first() else {
+        println!(
+            "{cmd}: usage: search <strategy> [name=value ...] max=MAX and/or time=DURATION\n{cmd}: strategies [default parameters]:\n{}",
+            with_registry(|registry| registry.help())
+        );
+        return;
+    };
+    let mut progress = menu_progress(cmd);
+    if let Some(trace) = &mut progress.0.trace {
+        trace.strategy = name.to_string();
+    }
+    let result = split_name_values(args)
+        .and_then(|args| search_by_name(name, &args, assignments, rng, &mut progress));
+    match result {
+        Ok(outcome) => {
+            println!(
+                "{cmd}: {name}: After {} steps ({}), {}-forbidden-assignment backuped.",
+                outcome.steps, outcome.stop, outcome.forbidden
+            );
+            print!("{}", outcome.report);
+        }
+        Err(err) => print_error(format!("{cmd}: {err}")),
+    }
+}
+
+// `snapshot_name` returns `arg` or (if empty) reads the snapshot name from input
+fn snapshot_name(cmd: &str, arg: &str) -> Option<String> {
+    let name = if arg.is_empty() {
+        println!("{cmd}: input: name of snapshot");
+        read_line().trim().to_string()
+    } else {
+        arg.to_string()
+    };
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        print_error(format!(
+            "{cmd}: Invalid snapshot name `{name}` (one word expected) !!!"
+        ));
+        return None;
+    }
+    Some(name)
+}
+
+pub fn snap(assignments: &mut Assignments, arg: &str) {
+    let cmd = "snap";
+    if let Some(name) = snapshot_name(cmd, arg) {
+        if assignments.snapshot_save(&name) {
+            println!("{cmd}: Snapshot `{name}` overwritten.");
+        } else {
+            println!("{cmd}: Snapshot `{name}` saved.");
+        }
+    }
+}
+
+pub fn snaps(assignments: &Assignments) {
+    let cmd = "snaps";
+    if assignments.snapshots().is_empty() {
+        println!("{cmd}: There are no snapshots.");
+        return;
+    }
+    println!("{cmd}: (name: forbidden, max left overlap, max right overlap)");
+    println!("  {:<16} {}", "(current)", assignments.metrics());
+    for name in assignments.snapshots().keys() {
+        if let Some(metrics) = assignments.snapshot_metrics(name) {
+            println!("  {:<16} {}", name, metrics);
+        }
+    }
+}
+
+pub fn restore(assignments: &mut Assignments, arg: &str) {
+    let cmd = "restore";
+    if let Some(name) = snapshot_name(cmd, arg) {
+        match assignments.snapshot_restore(&name) {
+            Ok(()) => println!("{cmd}: Snapshot `{name}` restored."),
+            Err(err) => print_error(format!("{cmd}: {err}")),
+        }
+    }
+}
+
+pub fn diffsnap(assignments: &Assignments, arg: &str) {
+    let cmd = "diffsnap";
+    let names: Vec<String> = if arg.is_empty() {
+        println!("{cmd}: input: names of two snapshots");
+        read_line().split_whitespace().map(String::from).collect()
+    } else {
+        arg.split_whitespace().map(String::from).collect()
+    };
+    if names.len() != 2 {
+        print_error(format!("{cmd}: Two snapshot names expected !!!"));
+        return;
+    }
+    let (a, b) = (&names[0], &names[1]);
+    let snapshots = assignments.snapshots();
+    let (Some(pairs_a), Some(pairs_b)) = (snapshots.get(a), snapshots.get(b)) else {
+        print_error(format!("{cmd}: There is no snapshot `{a}` or `{b}` !!!"));
+        return;
+    };
+    let forbidden = assignments.forbidden();
+    let diff = AssignmentsDiff::of(pairs_a, forbidden, pairs_b, forbidden);
+    println!(
+        "{cmd}: {} common pairs, {} pairs only in `{a}`, {} pairs only in `{b}`",
+        diff.common,
+        diff.removed.len(),
+        diff.added.len()
+    );
+    for (name, pairs) in [(a, &diff.removed), (b, &diff.added)] {
+        println!("  only in `{name}` (l r, `!!!` - forbidden):");
+        for &(l, r, forbidden) in pairs {
+            let warn = if forbidden { " !!!" } else { "" };
+            println!("    {l} {r}{warn}");
+        }
+    }
+    for name in [a, b] {
+        if let Some(metrics) = assignments.snapshot_metrics(name) {
+            println!("  {:<16} {}", name, metrics);
+        }
+    }
+}
+
+// `repair` - valid assignments changing as few current pairs as possible (see `repair::repair_pairs`)
+pub fn repair(assignments: &mut Assignments) {
+    let cmd = "repair";
+    match assignments.repaired() {
+        Ok((pairs, diff)) => {
+            if diff.is_empty() {
+                println!("{cmd}: Nothing to repair, the assignments are kept.");
+                return;
+            }
+            assignments.set_pairs_of_ids(&pairs);
+            assignments.f_min_backup_update(assignments.get_pairs_of_ids());
+            assignments.pool_offer(&pairs, assignments.number_of_forbidden_used());
+            println!("{cmd}: {}", diff.header());
+            println!("{cmd}: lefts affected: {:?}", diff.lefts());
+            print!("{}", diff.body(false));
+            println!("{cmd}: Repaired assignments set.");
+        }
+        Err(err) => print_error(format!("{cmd}: {err}")),
+    }
+}
+
+// `resize` - `rml <ids> [k=K]`, `rmr <ids> [k=K]`, `addl [count] [k=K]`, `addr [count] [k=K]`
+// removes or adds lefts or rights keeping as many pairs as possible (see `resize::Assignments::resized`)
+pub fn resize(assignments: &mut Assignments, cmd: &str, arg: &str) {
+    let mut ids: Vec<usize> = vec![];
+    let mut k = None;
+    for word in arg.split_whitespace() {
+        let parsed = match word.strip_prefix("k=") {
+            Some(value) => value.parse().map(|x| k = Some(x)),
+            None => word.parse().map(|x| ids.push(x)),
+        };
+        if let Err(err) = parsed {
+            print_error(format!("{cmd}: `{word}`: {err}"));
+            return;
+        }
+    }
+    let count = *ids.first().unwrap_or(&1);
+    let result = match cmd {
+        "rml" => assignments.without_lefts(&ids, k),
+        "rmr" => assignments.without_rights(&ids, k),
+        "addl" => assignments.with_new_lefts(count, k),
+        _ => assignments.with_new_rights(count, k),
+    };
+    match result {
+        Ok((resized, resize)) => {
+            *assignments = resized;
+            println!("{cmd}: {resize}.");
+            println!("{cmd}: {} set.", assignments.assignments_header());
+        }
+        Err(err) => print_error(format!("{cmd}: {err}")),
+    }
+}
+
+// `picture` - `dot <path>` or `svg <path>` writes the picture of the assignments (see `export`)
+pub fn picture(assignments: &Assignments, cmd: &str, path: &str) {
+    if path.is_empty() {
+        print_error(format!("{cmd}: `{cmd} <path>` expected !!!"));
+        return;
+    }
+    let out = match cmd {
+        "dot" => assignments.to_dot(assignments.labels()),
+        _ => assignments.to_svg(assignments.labels()),
+    };
+    match std::fs::write(path, out) {
+        Ok(()) => println!("{cmd}: Picture written to {path}."),
+        Err(err) => print_error(format!("{cmd}: {path}: {err}")),
+    }
+}
+
+// `split` - `split <N>|<tracks>|<file> [k=K]` prints the tracks solved separately and their combined
+// pairs (see `split::Assignments::split`, the current assignments are kept)
+pub fn split(assignments: &Assignments, arg: &str) {
+    let cmd = "split";
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    let Some((spec, args)) = words.split_first() else {
+        print_error(format!(
+            "{cmd}: `split <N>|<L,L,.../R,R,...;...>|<file> [k=K]` expected !!!"
+        ));
+        return;
+    };
+    let result = split_name_values(args).and_then(|args| {
+        let mut k = None;
+        for (name, value) in &args {
+            match name.as_str() {
+                "k" => k = Some(value.parse()?),
+                _ => return Err(format!("unknown parameter `{name}` (parameters: k) !!!").into()),
+            }
+        }
+        assignments.split(&Partition::parse(spec)?, assignments.labels(), k)
+    });
+    match result {
+        Ok(split) => {
+            println!("{cmd}: {}", split.to_string().replace('\n', "\nsplit: "));
+            print!("{}", split.body());
+        }
+        Err(err) => print_error(format!("{cmd}: {err}")),
+    }
+}
+
+// `merge` - `merge <file> [name=value ...]` merges the assignments of a JSON file into the current ones
+// (see `merge::merge`)
+pub fn merge(assignments: &mut Assignments, arg: &str) {
+    let cmd = "merge";
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    let Some((path, args)) = words.split_first() else {
+        print_error(format!(
+            "{cmd}: `merge <file> [name=value ...]` expected !!!"
+        ));
+        return;
+    };
+    let mut options = MergeOptions::default();
+    let result = split_name_values(args)
+        .and_then(|args| {
+            for (name, value) in &args {
+                options.set_parameter(name, value)?;
+            }
+            Ok(())
+        })
+        .and_then(|_| read_serde_file(path))
+        .and_then(|data| {
+            let second = Assignments::from(&data);
+            second.test_assignments()?;
+            merge::merge(assignments, &second, &options)
+        });
+    match result {
+        Ok(merged) => {
+            println!("{cmd}: {merged}");
+            if !merged.conflicts.is_empty() {
+                print!(
+                    "{cmd}: conflicts (l r assigned only by):\n{}",
+                    merged.conflicts_body()
+                );
+            }
+            println!(
+                "{cmd}: ids of {path}: lefts -> {:?}, rights -> {:?}",
+                merged.left_map, merged.right_map
+            );
+            *assignments = merged.assignments;
+            println!("{cmd}: Merged assignments set.");
+        }
+        Err(err) => print_error(format!("{cmd}: {err}")),
+    }
+}
+
+// `diff` - `diff <file>` changes from the assignments of a JSON file to the current ones
+// (see `diff::AssignmentsDiff`)
+pub fn diff(assignments: &Assignments, arg: &str) {
+    let cmd = "diff";
+    let path = if arg.is_empty() {
+        println!("{cmd}: input: path of JSON assignments");
+        read_line().trim().to_string()
+    } else {
+        arg.to_string()
+    };
+    match read_assignments_file(&path) {
+        Ok(other) => {
+            if other.get_kmn() != assignments.get_kmn() {
+                println!(
+                    "{cmd}: (k, m, n) = {:?} of {path} differ from {:?} !!!",
+                    other.get_kmn(),
+                    assignments.get_kmn()
+                );
+            }
+            let diff = other.diff(assignments);
+            println!("{cmd}: from {path} to current: {diff}");
+        }
+        Err(err) => print_error(format!("{cmd}: {path}: {err}")),
+    }
+}
+
+// `iso` - `iso [<snapshot>|<file>] [fixed=none|lefts|rights|both]` compares the current assignments
+// with a snapshot or with assignments of a JSON file up to relabeling (see `iso::canonical_form`)
+pub fn iso(assignments: &Assignments, arg: &str) {
+    let cmd = "iso";
+    let mut fixed = Fixed::None;
+    let mut target = None;
+    for word in arg.split_whitespace() {
+        match word.strip_prefix("fixed=") {
+            Some(value) => match value.parse() {
+                Ok(value) => fixed = value,
+                Err(err) => {
+                    print_error(format!("{cmd}: {err}"));
+                    return;
+                }
+            },
+            None => target = Some(word),
+        }
+    }
+    let canonical = match assignments.canonical_form(fixed) {
+        Ok(canonical) => canonical,
+        Err(err) => {
+            print_error(format!("{cmd}: {err}"));
+            return;
+        }
+    };
+    println!(
+        "{cmd}: canonical hash of the current assignments {canonical} ({} search nodes)",
+        canonical.nodes
+    );
+    let Some(target) = target else {
+        return;
+    };
+    let pairs = match assignments.snapshots().get(target) {
+        Some(pairs) => pairs.clone(),
+        None => match read_assignments_file(target) {
+            Ok(other) if other.get_kmn() == assignments.get_kmn() => other.get_pairs_of_ids(),
+            Ok(other) => {
+                print_error(format!(
+                    "{cmd}: (k, m, n) = {:?} of `{target}` differ from {:?} !!!",
+                    other.get_kmn(),
+                    assignments.get_kmn()
+                ));
+                return;
+            }
+            Err(err) => {
+                print_error(format!(
+                    "{cmd}: no snapshot `{target}`, file {target}: {err}"
+                ));
+                return;
+            }
+        },
+    };
+    match assignments.is_isomorphic(&pairs, fixed) {
+        Ok(true) => println!("{cmd}: `{target}` is isomorphic to the current assignments."),
+        Ok(false) => println!("{cmd}: `{target}` is NOT isomorphic to the current assignments."),
+        Err(err) => print_error(format!("{cmd}: {err}")),
+    }
+}
+
+// `pool_index` returns the index parsed from `arg` (or from input if `arg` is empty)
+fn pool_index(cmd: &str, arg: &str) -> Option<usize> {
+    let input = if arg.is_empty() {
+        println!("{cmd}: input: index of pool entry (see `pool`)");
+        read_line()
+    } else {
+        arg.to_string()
+    };
     match split_and_parse_input::<usize>(&input, 1) {
+        Ok(args) => Some(args[0]),
+        Err(err) => {
+            print_error(format!("{cmd}: {err}"));
+            None
+        }
+    }
+}
+
+pub fn pool(assignments: &Assignments) {
+    let cmd = "pool";
+    let pool = assignments.pool();
+    println!(
+        "{cmd}: {} / {} best distinct assignments found (index: forbidden, max left overlap, max right overlap)",
+        pool.entries().len(),
+        pool.capacity()
+    );
+    let current = {
+        let mut pairs = assignments.get_pairs_of_ids();
+        pairs.sort();
+        pairs
+    };
+    for (i, entry) in pool.entries().iter().enumerate() {
+        let mark = if entry.pairs == current {
+            "  <- current"
+        } else {
+            ""
+        };
+        let stale = if entry.version != assignments.forbidden_version() {
+            "  (stale)"
+        } else {
+            ""
+        };
+        println!("  {:>3}  {}{}{}", i, entry.metrics, stale, mark);
+    }
+}
+
+pub fn poolp(assignments: &Assignments, arg: &str) {
+    let cmd = "poolp";
+    if let Some(i) = pool_index(cmd, arg) {
+        match assignments.pool().entries().get(i) {
+            Some(entry) => {
+                let stale = if entry.version != assignments.forbidden_version() {
+                    " (stale: evaluated against old forbidden pairs)"
+                } else {
+                    ""
+                };
+                println!("{cmd}: pool entry {i}: {}{stale}", entry.metrics);
+                println!("  (l r, `!!!` - forbidden) [");
+                for &(l, r) in &entry.pairs {
+                    let warn = if assignments.forbidden.contains(&(l, r)) {
+                        " !!!"
+                    } else {
+                        ""
+                    };
+                    println!("    {l} {r}{warn}");
+                }
+                println!("  ]");
+            }
+            None => print_error(format!("{cmd}: There is no pool entry {i} !!!")),
+        }
+    }
+}
+
+pub fn poolr(assignments: &mut Assignments, arg: &str) {
+    let cmd = "poolr";
+    if let Some(i) = pool_index(cmd, arg) {
+        match assignments.pool_restore(i) {
+            Ok(()) => println!("{cmd}: Pool entry {i} restored."),
+            Err(err) => print_error(format!("{cmd}: {err}")),
+        }
+    }
+}
+
+pub fn poolcap(assignments: &mut Assignments, arg: &str) {
+    let cmd = "poolcap";
+    let input = if arg.is_empty() {
+        println!("{cmd}: input: capacity of the pool (1 <= capacity)");
+        read_line()
+    } else {
+        arg.to_string()
+    };
+    match split_and_parse_input::<usize>(&input, 1) {
+        Ok(args) if args[0] >= 1 => {
+            assignments.pool_set_capacity(args[0]);
+            println!("{cmd}: Pool capacity set to {}.", args[0]);
+        }
+        Ok(_) => print_error(format!("{cmd}: Capacity must be at least 1 !!!")),
+        Err(err) => print_error(format!("{cmd}: {err}")),
+    }
+}
+
+pub fn bsr(assignments: &mut Assignments, arg: &str, rng: &mut impl Rng) {
+    let cmd = "bsr";
+    let Some(time) = search_time(cmd, arg) else {
+        return;
+    };
+    match search_input(cmd, time, false) {
         Ok(args) => {
             let max = args[0];
-            println!("max = {}", max);
-            let (Steps(l_steps), Steps(r_steps), Forbidden(f)) =
-                assignments.random_back_swaps(Side::Right, max, rng);
-            println!(
-                "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
-                cmd, l_steps, r_steps, f
-            );
+            if time.is_none() {
+                println!("max = {}", max);
+            }
+            match BackSwap::search_with_progress(
+                assignments,
+                Side::Right,
+                Budget { max, time },
+                rng,
+                &mut menu_progress(cmd),
+            ) {
+                Ok((Steps(l_steps), Steps(r_steps), Forbidden(f))) => println!(
+                    "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
+                    cmd, l_steps, r_steps, f
+                ),
+                Err(err) => print_error(format!("{cmd}: {err}")),
+            }
         }
         Err(err) => {
-            println!("{cmd}: {}", err);
+            print_error(format!("{cmd}: {}", err));
             // continue 'cmd; // try again!
         }
     }
 }
 
-pub fn bslr(assignments: &mut Assignments, rng: &mut impl Rng) {
+pub fn bslr(assignments: &mut Assignments, arg: &str, rng: &mut impl Rng) {
     let cmd = "bslr";
-    println!(
-        "{}: input: max l_percent (0 <= max and 0<= l_percent <= 100): ",
-        cmd
-    );
-    let input = read_line();
-    match split_and_parse_input::<usize>(&input, 2) {
+    let Some(time) = search_time(cmd, arg) else {
+        return;
+    };
+    match search_input(cmd, time, true) {
         Ok(args) => {
             let (max, l_percent) = (args[0], args[1]);
-            println!("max = {}, l_percent = {}", max, l_percent);
-            let (Steps(l_steps), Steps(r_steps), Forbidden(f)) =
-                assignments.random_back_swaps(Side::LeftPercent(l_percent), max, rng);
-            println!(
-                "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
-                cmd, l_steps, r_steps, f
-            );
+            match time {
+                Some(_) => println!("l_percent = {}", l_percent),
+                None => println!("max = {}, l_percent = {}", max, l_percent),
+            }
+            match BackSwap::search_with_progress(
+                assignments,
+                Side::LeftPercent(l_percent),
+                Budget { max, time },
+                rng,
+                &mut menu_progress(cmd),
+            ) {
+                Ok((Steps(l_steps), Steps(r_steps), Forbidden(f))) => println!(
+                    "{}: After ({},{}) (left,right)-steps, {}-forbidden-assignment backuped.",
+                    cmd, l_steps, r_steps, f
+                ),
+                Err(err) => print_error(format!("{cmd}: {err}")),
+            }
         }
         Err(err) => {
-            println!("{cmd}: {}", err);
+            print_error(format!("{cmd}: {}", err));
             // continue 'cmd; // try again!
         }
     }
 }
 
+// `exact` - `exact [count] [nodes=N]` the exact solver (see `exact::ExactSolver`) for small instances
+pub fn exact(assignments: &mut Assignments, arg: &str) {
+    let cmd = "exact";
+    let mut solver = ExactSolver::default();
+    for word in arg.split_whitespace() {
+        match word.split_once('=') {
+            None if word == "count" => solver.count = true,
+            Some(("nodes", value)) => match value.parse() {
+                Ok(nodes) => solver.node_limit = nodes,
+                Err(err) => {
+                    print_error(format!("{cmd}: nodes={value}: {err}"));
+                    return;
+                }
+            },
+            _ => {
+                print_error(format!(
+                    "{cmd}: Unexpected argument `{word}` (expected: `count` or `nodes=N`) !!!"
+                ));
+                return;
+            }
+        }
+    }
+    match solver.solve(assignments) {
+        Ok(outcome) => {
+            assignments.set_pairs_of_ids(&outcome.pairs);
+            assignments.f_min_backup_update(assignments.get_pairs_of_ids());
+            assignments.pool_offer(&outcome.pairs, outcome.forbidden);
+            println!("{cmd}: {outcome}, optimal assignments set.");
+        }
+        Err(err) => print_error(format!("{cmd}: {err}")),
+    }
+}
+
+// `cp` - `cp [name=value ...]` the constraint programming solver (see `cp::CpSolver::set_parameter`)
+pub fn cp(assignments: &mut Assignments, arg: &str) {
+    let cmd = "cp";
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    let mut solver = CpSolver::default();
+    let result = split_name_values(&words)
+        .and_then(|args| {
+            for (name, value) in &args {
+                solver.set_parameter(name, value)?;
+            }
+            Ok(())
+        })
+        .and_then(|_| solver.solve(assignments));
+    match result {
+        Ok(outcome) => {
+            println!("{cmd}: {outcome}.");
+            if let Some(pairs) = &outcome.pairs {
+                assignments.set_pairs_of_ids(pairs);
+                assignments.f_min_backup_update(assignments.get_pairs_of_ids());
+                assignments.pool_offer(pairs, assignments.number_of_forbidden_used());
+                println!("{cmd}: The best assignments found set.");
+            }
+        }
+        Err(err) => print_error(format!("{cmd}: {err}")),
+    }
+}
+
+// `sample` - `sample [steps=N]` uniformly random valid assignments (see `sample::sample_uniform`)
+pub fn sample(assignments: &mut Assignments, arg: &str, rng: &mut impl Rng) {
+    let cmd = "sample";
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    let mut steps = MIXING_PER_PAIR * assignments.get_pairs_of_ids().len();
+    let result = split_name_values(&words)
+        .and_then(|args| {
+            for (name, value) in &args {
+                match name.as_str() {
+                    "steps" => steps = value.parse()?,
+                    _ => {
+                        return Err(
+                            format!("unknown parameter `{name}` (parameters: steps) !!!").into(),
+                        );
+                    }
+                }
+            }
+            Ok(())
+        })
+        .and_then(|_| sample_uniform(assignments, steps, rng));
+    match result {
+        Ok((pairs, stats)) => {
+            assignments.set_pairs_of_ids(&pairs);
+            assignments.f_min_backup_update(assignments.get_pairs_of_ids());
+            assignments.pool_offer(&pairs, assignments.number_of_forbidden_used());
+            println!("{cmd}: {stats}, random valid assignments set.");
+        }
+        Err(err) => print_error(format!("{cmd}: {err}")),
+    }
+}
+
+// `cover` - `cover [steps=N]` assignments covering as many pairs of rights as possible
+// (see `cover::improve_coverage`)
+pub fn cover(assignments: &mut Assignments, arg: &str, rng: &mut impl Rng) {
+    let cmd = "cover";
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    let mut steps = COVER_STEPS_PER_PAIR * assignments.get_pairs_of_ids().len();
+    let before = assignments.metrics();
+    let result = split_name_values(&words)
+        .and_then(|args| {
+            for (name, value) in &args {
+                match name.as_str() {
+                    "steps" => steps = value.parse()?,
+                    _ => {
+                        return Err(
+                            format!("unknown parameter `{name}` (parameters: steps) !!!").into(),
+                        );
+                    }
+                }
+            }
+            Ok(())
+        })
+        .and_then(|_| improve_coverage(assignments, steps, rng));
+    match result {
+        Ok((pairs, stats)) => {
+            assignments.set_pairs_of_ids(&pairs);
+            assignments.f_min_backup_update(assignments.get_pairs_of_ids());
+            assignments.pool_offer(&pairs, assignments.number_of_forbidden_used());
+            println!(
+                "{cmd}: {stats} (before {}/{}).",
+                before.covered_right_pairs, before.right_pairs
+            );
+        }
+        Err(err) => print_error(format!("{cmd}: {err}")),
+    }
+}
+
 pub fn sbrk(assignments: &mut Assignments) {
     let cmd = "sbrk";
     let pairs = assignments.get_pairs_of_ids();
@@ -278,7 +1126,7 @@ pub fn sbrk(assignments: &mut Assignments) {
             assignments.set_pairs_of_ids(&pairs);
             if let Err(err) = assignments.test_assignments() {
                 println!("{}", assignments);
-                println!("{cmd}: {}", err);
+                print_error(format!("{cmd}: {}", err));
                 assignments.set_pairs_of_ids(&tmp);
                 println!("{cmd}: Old restored!");
             } else {
@@ -286,7 +1134,7 @@ pub fn sbrk(assignments: &mut Assignments) {
             };
         }
         Err(err) => {
-            println!("{}", err)
+            print_error(err);
         }
     }
 }
@@ -300,9 +1148,9 @@ pub fn df(assignments: &mut Assignments) {
         Ok(args) => {
             let (l, r) = (args[0], args[1]);
             if !(l < m && r < n) {
-                println!(
+                print_error(format!(
                     "You have input (l, r)=({l}, {r}) that does not meet the condition: 0 <= l < {m} and 0 <= r < {n} !!!",
-                );
+                ));
                 // break;
             } else {
                 // Ok, do "df"
@@ -317,7 +1165,7 @@ pub fn df(assignments: &mut Assignments) {
             }
         }
         Err(err) => {
-            println!("{cmd}: {}", err);
+            print_error(format!("{cmd}: {}", err));
             // break; // stop the `af` command
         }
     }
@@ -333,9 +1181,9 @@ pub fn dfl(assignments: &mut Assignments) {
             Ok(args) => {
                 let l = args[0];
                 if !(l < m) {
-                    println!(
+                    print_error(format!(
                         "You have input l ={l} that does not meet the condition: 0 <= l < {m} !!!",
-                    );
+                    ));
                     // break;
                 } else {
                     // Ok, do "df"
@@ -370,9 +1218,9 @@ pub fn dfr(assignments: &mut Assignments) {
             Ok(args) => {
                 let r = args[0];
                 if !(r < n) {
-                    println!(
+                    print_error(format!(
                         "You have input r ={r} that does not meet the condition: 0 <= r < {n} !!!",
-                    );
+                    ));
                     // break;
                 } else {
                     // Ok, do "df"
@@ -410,9 +1258,9 @@ pub fn af(assignments: &mut Assignments) {
             Ok(args) => {
                 let (l, r) = (args[0], args[1]);
                 if !(l < m && r < n) {
-                    println!(
+                    print_error(format!(
                         "{cmd}: You have input (l, r)=({l}, {r}) that does not meet the condition: 0 <= l < {m} and 0 <= r < {n} !!!",
-                    );
+                    ));
                     break 'af;
                 } else {
                     // Ok, do "af"
@@ -453,7 +1301,7 @@ pub fn arf(assignments: &mut Assignments, rng: &mut impl Rng) {
             println!("{}: added {} random forbidden.", cmd, count);
         }
         Err(err) => {
-            println!("{cmd}: {}", err);
+            print_error(format!("{cmd}: {}", err));
             // continue 'cmd; // try again!
         }
     }
@@ -480,11 +1328,11 @@ pub fn arfl(assignments: &mut Assignments, rng: &mut impl Rng) {
                 }
                 println!("{}: added {} random forbidden.", cmd, count);
             } else {
-                println!("{}: Bad input: l = {} >= {} !!!", cmd, l, m);
+                print_error(format!("{}: Bad input: l = {} >= {} !!!", cmd, l, m));
             }
         }
         Err(err) => {
-            println!("{cmd}: {}", err);
+            print_error(format!("{cmd}: {}", err));
             // continue 'cmd; // try again!
         }
     }
@@ -511,11 +1359,11 @@ pub fn arfr(assignments: &mut Assignments, rng: &mut impl Rng) {
                 }
                 println!("{}: added {} random forbidden.", cmd, count);
             } else {
-                println!("{}: Bad input: r = {} >= {} !!!", cmd, r, n);
+                print_error(format!("{}: Bad input: r = {} >= {} !!!", cmd, r, n));
             }
         }
         Err(err) => {
-            println!("{cmd}: {}", err);
+            print_error(format!("{cmd}: {}", err));
             // continue 'cmd; // try again!
         }
     }
@@ -536,9 +1384,9 @@ pub fn aflvrvj(assignments: &mut Assignments) {
                 // 'af1: for (l, r) in pairs {
                 for (l, r) in pairs {
                     if !(l < m && r < n) {
-                        println!(
+                        print_error(format!(
                             "{cmd}: You have input (l, r)=({l}, {r}) that does not meet the condition: 0 <= l < {m} and 0 <= r < {n} !!!",
-                        );
+                        ));
                         // break 'af1; // should we continue?
                     } else {
                         // Ok, do "af"
@@ -0,0 +1,319 @@
+// Merging two assignments prepared for disjoint or overlapping sets of lefts and rights: the ids of the
+// second are mapped to the merged ids (the first keeps its ids), the forbidden pairs are united, the pairs
+// assigned by only one of them for a shared left and right are reported as conflicts, and the merged pairs
+// are repaired to the degree rules and tested
+
+use crate::kmn_serde::*;
+use crate::repair::*;
+use crate::*;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+// IdMapping - how the ids of one side of the second assignments map to the merged ids
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum IdMapping {
+    #[default]
+    Same, // the same ids are the same elements
+    Disjoint,                         // the second ids follow the first ones
+    Labels,                           // the same labels are the same elements
+    Explicit(BTreeMap<usize, usize>), // `id:merged_id,...`, the other ids follow the first ones
+}
+
+impl FromStr for IdMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "same" => Ok(IdMapping::Same),
+            "disjoint" => Ok(IdMapping::Disjoint),
+            "labels" => Ok(IdMapping::Labels),
+            _ => {
+                let mut map = BTreeMap::new();
+                for item in s.split(',') {
+                    let parsed = item
+                        .split_once(':')
+                        .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)));
+                    let Some((x, y)) = parsed else {
+                        return Err(format!(
+                            "bad id mapping `{s}` (same, disjoint, labels or ID:MERGED_ID,...) !!!"
+                        ));
+                    };
+                    map.insert(x, y);
+                }
+                Ok(IdMapping::Explicit(map))
+            }
+        }
+    }
+}
+
+// MergeOptions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeOptions {
+    pub lefts: IdMapping,
+    pub rights: IdMapping,
+    pub k: Option<usize>, // k of the merged assignments (the greater k by default)
+    pub prefer_second: bool, // conflicts resolved by the second assignments (by the first by default)
+}
+
+impl MergeOptions {
+    // `set_parameter` - lefts=MAPPING, rights=MAPPING, k=K, prefer=first|second
+    pub fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        match name {
+            "lefts" => self.lefts = value.parse()?,
+            "rights" => self.rights = value.parse()?,
+            "k" => self.k = Some(value.parse()?),
+            "prefer" => {
+                self.prefer_second = match value {
+                    "first" => false,
+                    "second" => true,
+                    _ => return Err(format!("bad prefer `{value}` (first, second) !!!").into()),
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "unknown parameter `{name}` (parameters: lefts, rights, k, prefer) !!!"
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+// Merged - the merged assignments (tested, with the merged labels) with the mapping of the second
+// ids and the conflicts
+#[derive(Debug, Clone)]
+pub struct Merged {
+    pub assignments: Assignments,
+    pub left_map: Vec<usize>,  // second left id -> merged left id
+    pub right_map: Vec<usize>, // second right id -> merged right id
+    pub conflicts: Vec<(usize, usize, bool)>, // merged (l, r) assigned only by the first (true) or the second
+    pub kept: usize,  // merged pairs of the first or of the second assignments
+    pub added: usize, // merged pairs of neither (restoring the degree rules)
+}
+
+impl fmt::Display for Merged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, {} pairs kept, {} added, {} conflicts",
+            self.assignments.assignments_header(),
+            self.kept,
+            self.added,
+            self.conflicts.len()
+        )
+    }
+}
+
+impl Merged {
+    // `conflicts_body` - lines `l r first` or `l r second` (the assignments assigning the pair)
+    pub fn conflicts_body(&self) -> String {
+        let mut out = String::from("  [\n");
+        for &(l, r, first) in &self.conflicts {
+            let by = if first { "first" } else { "second" };
+            out = format!("{}    {} {} {}\n", out, l, r, by);
+        }
+        out = format!("{}  ]\n", out);
+        out
+    }
+
+    // JSON assignments data with the merged labels
+    pub fn serde(&self) -> SerdeKmnAssignment {
+        SerdeKmnAssignment::from(&self.assignments)
+    }
+}
+
+// SideMap - merged length, merged labels and second id -> merged id of one side
+type SideMap = (usize, Vec<String>, Vec<usize>);
+
+// `map_ids` of one side
+fn map_ids(
+    side: &str,
+    mapping: &IdMapping,
+    (len1, labels1): (usize, &[String]),
+    (len2, labels2): (usize, &[String]),
+) -> Result<SideMap, Box<dyn Error>> {
+    let mut map = vec![];
+    let mut labels = labels1.to_vec();
+    let mut len = len1;
+    match mapping {
+        IdMapping::Same => {
+            map = (0..len2).collect();
+            len = len1.max(len2);
+            if labels.len() == len1 && labels2.len() == len2 {
+                labels.extend(labels2.iter().skip(len1).cloned());
+            }
+        }
+        IdMapping::Disjoint => {
+            map = (len1..len1 + len2).collect();
+            len = len1 + len2;
+            labels.extend(labels2.iter().cloned());
+        }
+        IdMapping::Labels => {
+            if labels1.len() != len1 || labels2.len() != len2 {
+                return Err(format!("labels of all the {side} needed !!!").into());
+            }
+            for label in labels2 {
+                match labels.iter().position(|x| x == label) {
+                    Some(i) => map.push(i),
+                    None => {
+                        labels.push(label.clone());
+                        map.push(len);
+                        len += 1;
+                    }
+                }
+            }
+        }
+        IdMapping::Explicit(explicit) => {
+            for i in 0..len2 {
+                match explicit.get(&i) {
+                    Some(&j) if j < len1 => map.push(j),
+                    Some(&j) => {
+                        return Err(format!("{side} map {i}:{j} to {j} >= {len1} !!!").into());
+                    }
+                    None => {
+                        map.push(len);
+                        len += 1;
+                        labels.extend(labels2.get(i).cloned());
+                    }
+                }
+            }
+            if let Some(i) = explicit.keys().find(|i| **i >= len2) {
+                return Err(format!("{side} map of {i} >= {len2} !!!").into());
+            }
+        }
+    }
+    let mut sorted = map.clone();
+    sorted.sort();
+    sorted.dedup();
+    if sorted.len() != map.len() {
+        return Err(format!("two {side} of the second map to the same id !!!").into());
+    }
+    if labels.len() != len {
+        labels.clear(); // labels of some ids unknown
+    }
+    Ok((len, labels, map))
+}
+
+// `merge` the `second` assignments into the `first` (both with their labels)
+pub fn merge(
+    first: &Assignments,
+    second: &Assignments,
+    options: &MergeOptions,
+) -> Result<Merged, Box<dyn Error>> {
+    let (k1, m1, n1) = first.get_kmn();
+    let (k2, m2, n2) = second.get_kmn();
+    let (labels1, labels2) = (first.labels(), second.labels());
+    // the ids of the forbidden pairs are mapped below
+    for (name, assignments, m, n) in [("first", first, m1, n1), ("second", second, m2, n2)] {
+        if let Some((l, r)) = assignments
+            .forbidden()
+            .iter()
+            .find(|(l, r)| *l >= m || *r >= n)
+        {
+            return Err(
+                format!("{name} assignments: forbidden ({l}, {r}) out of range !!!").into(),
+            );
+        }
+    }
+    let (m, left_labels, left_map) = map_ids(
+        "lefts",
+        &options.lefts,
+        (m1, &labels1.lefts),
+        (m2, &labels2.lefts),
+    )?;
+    let (n, right_labels, right_map) = map_ids(
+        "rights",
+        &options.rights,
+        (n1, &labels1.rights),
+        (n2, &labels2.rights),
+    )?;
+    let mapped = |pairs: &Vec<(usize, usize)>| -> Vec<(usize, usize)> {
+        pairs
+            .iter()
+            .map(|(l, r)| (left_map[*l], right_map[*r]))
+            .collect()
+    };
+    let pairs1 = first.get_pairs_of_ids();
+    let pairs2 = mapped(&second.get_pairs_of_ids());
+    let mut forbidden = first.forbidden().clone();
+    for pair in mapped(second.forbidden()) {
+        if !forbidden.contains(&pair) {
+            forbidden.push(pair);
+        }
+    }
+    // a shared left and right assigned by only one of the assignments
+    let shared = |(l, r): (usize, usize)| {
+        l < m1 && left_map.contains(&l) && r < n1 && right_map.contains(&r)
+    };
+    let mut conflicts: Vec<(usize, usize, bool)> = pairs1
+        .iter()
+        .filter(|x| shared(**x) && !pairs2.contains(x))
+        .map(|&(l, r)| (l, r, true))
+        .chain(
+            pairs2
+                .iter()
+                .filter(|x| shared(**x) && !pairs1.contains(x))
+                .map(|&(l, r)| (l, r, false)),
+        )
+        .collect();
+    conflicts.sort();
+    // the pairs of both without the conflicts lost by the preferred assignments
+    let mut baseline: Vec<(usize, usize)> = pairs1.clone();
+    baseline.extend(pairs2.iter().filter(|x| !pairs1.contains(x)));
+    baseline.retain(|&(l, r)| {
+        !conflicts
+            .iter()
+            .any(|&(l1, r1, first)| (l1, r1) == (l, r) && first == options.prefer_second)
+    });
+    let k = options.k.unwrap_or(k1.max(k2));
+    if !(1 <= k && k <= m) {
+        return Err(
+            format!("1 <= k <= m is not satisfied for (k, m, n) = ({k}, {m}, {n}) !!!").into(),
+        );
+    }
+    let mut assignments = Assignments::new(k, m, n);
+    let p = assignments.p();
+    if p > n {
+        return Err(format!("p = {p} > n = {n} for (k, m, n) = ({k}, {m}, {n}) !!!").into());
+    }
+    let pairs = repair_pairs(k, m, n, p, &baseline, &forbidden)
+        .ok_or("no assignments satisfy the degree rules !!!")?;
+    let kept = pairs.iter().filter(|x| baseline.contains(x)).count();
+    assignments.forbidden = forbidden;
+    assignments.set_labels(Labels {
+        lefts: left_labels,
+        rights: right_labels,
+    });
+    assignments.set_pairs_of_ids(&pairs);
+    assignments
+        .test_assignments()
+        .map_err(|err| format!("merged assignments:\n{err}"))?;
+    Ok(Merged {
+        assignments,
+        left_map,
+        right_map,
+        conflicts,
+        kept,
+        added: pairs.len() - kept,
+    })
+}
+
+// `merge_data` - `merge` of JSON assignments data (e.g. of files) with their labels
+pub fn merge_data(
+    first: &SerdeKmnAssignment,
+    second: &SerdeKmnAssignment,
+    options: &MergeOptions,
+) -> Result<Merged, Box<dyn Error>> {
+    let (mut assignments1, mut assignments2) =
+        (Assignments::from(first), Assignments::from(second));
+    for (name, assignments) in [("first", &mut assignments1), ("second", &mut assignments2)] {
+        assignments
+            .test_assignments()
+            .map_err(|err| format!("{name} assignments:\n{err}"))?;
+        assignments
+            .test_forbidden()
+            .map_err(|err| format!("{name} assignments:\n{err}"))?;
+    }
+    merge(&assignments1, &assignments2, options)
+}
@@ -0,0 +1,116 @@
+// Metrics comparing candidate assignments (besides the number of forbidden pairs)
+
+use crate::*;
+
+// `Metrics` of assignments pairs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Metrics {
+    pub forbidden: usize,           // number of forbidden pairs in assignments
+    pub max_left_overlap: usize,    // max number of rights shared by two lefts
+    pub max_right_overlap: usize,   // max number of lefts shared by two rights
+    pub covered_right_pairs: usize, // pairs of rights assigned to a common left
+    pub right_pairs: usize,         // all the pairs of rights
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "forbidden {}, max left overlap {}, max right overlap {}, right pairs covered {}/{} ({:.1}%)",
+            self.forbidden,
+            self.max_left_overlap,
+            self.max_right_overlap,
+            self.covered_right_pairs,
+            self.right_pairs,
+            100.0 * self.coverage()
+        )
+    }
+}
+
+impl Metrics {
+    // `key` orders metrics from the best: fewer forbidden, then smaller overlaps
+    pub fn key(&self) -> (usize, usize, usize) {
+        (
+            self.forbidden,
+            self.max_left_overlap,
+            self.max_right_overlap,
+        )
+    }
+
+    // fraction of the pairs of rights assigned to a common left
+    pub fn coverage(&self) -> f64 {
+        if self.right_pairs == 0 {
+            return 1.0;
+        }
+        self.covered_right_pairs as f64 / self.right_pairs as f64
+    }
+
+    // `Metrics` of `pairs` with lefts in 0..m and rights in 0..n
+    pub fn of(
+        pairs: &Vec<(usize, usize)>,
+        forbidden: &Vec<(usize, usize)>,
+        m: usize,
+        n: usize,
+    ) -> Self {
+        let mut lefts_of_right: Vec<Vec<usize>> = vec![vec![]; n];
+        let mut rights_of_left: Vec<Vec<usize>> = vec![vec![]; m];
+        for &(l, r) in pairs {
+            lefts_of_right[r].push(l);
+            rights_of_left[l].push(r);
+        }
+        Self {
+            forbidden: intersection_size(pairs, forbidden),
+            max_left_overlap: max_overlap(&rights_of_left, &lefts_of_right),
+            max_right_overlap: max_overlap(&lefts_of_right, &rights_of_left),
+            covered_right_pairs: covered_pairs(&rights_of_left, n),
+            right_pairs: n * n.saturating_sub(1) / 2,
+        }
+    }
+}
+
+// `max_overlap` - max number of neighbors shared by two distinct x, y, where
+// `neighbors[x]` are the neighbors of x and `back[z]` are the neighbors of z (the other side)
+fn max_overlap(neighbors: &[Vec<usize>], back: &[Vec<usize>]) -> usize {
+    let mut shared = vec![0; neighbors.len()];
+    let mut max = 0;
+    for (x, x_neighbors) in neighbors.iter().enumerate() {
+        for &z in x_neighbors {
+            for &y in &back[z] {
+                if y > x {
+                    shared[y] += 1;
+                    max = max.max(shared[y]);
+                }
+            }
+        }
+        for &z in x_neighbors {
+            for &y in &back[z] {
+                shared[y] = 0;
+            }
+        }
+    }
+    max
+}
+
+// `covered_pairs` - number of pairs of distinct y < `len` in a common `neighbors[x]`
+pub fn covered_pairs(neighbors: &[Vec<usize>], len: usize) -> usize {
+    let mut xs_of: Vec<Vec<usize>> = vec![vec![]; len];
+    for (x, x_neighbors) in neighbors.iter().enumerate() {
+        for &y in x_neighbors {
+            xs_of[y].push(x);
+        }
+    }
+    // marked[y2] == y1 + 1 - the pair (y1, y2) is already counted (no `len * len` table)
+    let mut marked = vec![0; len];
+    let mut count = 0;
+    for (y1, y1_xs) in xs_of.iter().enumerate() {
+        for &x in y1_xs {
+            for &y2 in &neighbors[x] {
+                if y1 < y2 && marked[y2] != y1 + 1 {
+                    marked[y2] = y1 + 1;
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
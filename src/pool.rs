@@ -0,0 +1,105 @@
+// Pool of the best pairwise distinct assignments found by the searches
+
+use crate::metrics::*;
+
+// default number of assignments kept in `SolutionPool`
+pub const POOL_CAPACITY: usize = 10;
+
+// PoolEntry
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolEntry {
+    pub pairs: Vec<(usize, usize)>, // sorted assignments pairs
+    pub metrics: Metrics,
+    pub version: usize, // `forbidden_version` the metrics were evaluated against
+}
+
+// SolutionPool - entries sorted from the best (see `Metrics::key`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolutionPool {
+    entries: Vec<PoolEntry>,
+    capacity: usize,
+}
+
+impl SolutionPool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: vec![],
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn entries(&self) -> &Vec<PoolEntry> {
+        &self.entries
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // `set_capacity` drops the worst entries above the new `capacity`
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.entries.truncate(self.capacity);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    // `may_accept` - quick check if assignments with `forbidden` pairs could enter the pool
+    pub fn may_accept(&self, forbidden: usize) -> bool {
+        match self.entries.last() {
+            Some(worst) if self.entries.len() >= self.capacity => {
+                forbidden <= worst.metrics.forbidden
+            }
+            _ => true,
+        }
+    }
+
+    // `offer` adds `pairs` (unless already contained) if it is better than the worst entry
+    // or the pool is not full and returns true if it was added
+    pub fn offer(&mut self, pairs: &[(usize, usize)], metrics: Metrics, version: usize) -> bool {
+        let mut pairs = pairs.to_vec();
+        pairs.sort();
+        if self.entries.iter().any(|x| x.pairs == pairs) {
+            return false;
+        }
+        // new entry goes after the entries at least as good as it
+        let i = self
+            .entries
+            .partition_point(|x| x.metrics.key() <= metrics.key());
+        if i >= self.capacity {
+            return false;
+        }
+        self.entries.insert(
+            i,
+            PoolEntry {
+                pairs,
+                metrics,
+                version,
+            },
+        );
+        self.entries.truncate(self.capacity);
+        true
+    }
+
+    // `rescore` re-evaluates the entries by `score` for `version` and sorts them again
+    // returns the number of entries with changed number of forbidden pairs
+    pub fn rescore<F: Fn(&Vec<(usize, usize)>) -> Metrics>(
+        &mut self,
+        score: F,
+        version: usize,
+    ) -> usize {
+        let mut changed = 0;
+        for entry in &mut self.entries {
+            let metrics = score(&entry.pairs);
+            if metrics.forbidden != entry.metrics.forbidden {
+                changed += 1;
+            }
+            entry.metrics = metrics;
+            entry.version = version;
+        }
+        self.entries.sort_by_key(|x| x.metrics.key()); // stable
+        changed
+    }
+}
@@ -0,0 +1,269 @@
+// Portfolio search scheduling the moves of the other searches (by weights or by an adaptive bandit)
+
+use crate::strategy::*;
+use crate::*;
+use rand::RngCore;
+
+// moves of the portfolio (named as the menu commands doing them repeatedly)
+pub const MOVES: [&str; 7] = ["rl", "rr", "sl", "sr", "bsl", "bsr", "sbrk"];
+
+// MoveStats - statistics of one move
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MoveStats {
+    pub used: usize,
+    pub improved: usize, // steps decreasing the number of forbidden in assignments
+    pub best: usize,     // steps finding a new best
+    pub failed: usize,   // steps that did not apply (e.g. no switching found)
+}
+
+// Portfolio
+pub struct Portfolio {
+    pub bandit: bool,      // choose by `scores` (else by `weights`)
+    pub weights: Vec<f64>, // for each of `MOVES`
+    pub epsilon: f64,      // bandit: probability of choosing by `weights`
+    pub decay: f64,        // bandit: weight of the old score
+    pub stall: usize,      // restart after `stall` steps without new best (0 - never)
+    scores: Vec<f64>,      // bandit: recency weighted improvements of each move
+    stats: Vec<MoveStats>,
+    restarts: usize,
+    best: usize,
+    since_best: usize,
+    last_move: &'static str,
+}
+
+impl Portfolio {
+    pub fn new() -> Self {
+        Self {
+            bandit: true,
+            weights: vec![1.0, 1.0, 4.0, 4.0, 2.0, 2.0, 1.0],
+            epsilon: 0.1,
+            decay: 0.9,
+            stall: 2000,
+            scores: vec![0.0; MOVES.len()],
+            stats: vec![MoveStats::default(); MOVES.len()],
+            restarts: 0,
+            best: 0,
+            since_best: 0,
+            last_move: "",
+        }
+    }
+
+    pub fn stats(&self) -> &Vec<MoveStats> {
+        &self.stats
+    }
+
+    // `choose_by_weights` - index of a move with probability proportional to its weight
+    fn choose_by_weights(&self, rng: &mut dyn RngCore) -> usize {
+        let sum: f64 = self.weights.iter().sum();
+        let mut x = rng.random_range(0.0..sum);
+        for (i, w) in self.weights.iter().enumerate() {
+            if x < *w {
+                return i;
+            }
+            x -= w;
+        }
+        self.weights.iter().rposition(|w| *w > 0.0).unwrap_or(0)
+    }
+
+    // `choose` - the move of the next step
+    fn choose(&self, rng: &mut dyn RngCore) -> usize {
+        if !self.bandit || rng.random::<f64>() < self.epsilon {
+            return self.choose_by_weights(rng);
+        }
+        // the best scored moves (with positive weights) - random one of them
+        let allowed = |i: &usize| self.weights[*i] > 0.0;
+        let max = (0..MOVES.len())
+            .filter(allowed)
+            .map(|i| self.scores[i])
+            .fold(f64::MIN, f64::max);
+        let best: Vec<usize> = (0..MOVES.len())
+            .filter(allowed)
+            .filter(|i| self.scores[*i] == max)
+            .collect();
+        best[rng.random_range(0..best.len())]
+    }
+
+    // `apply` move `i`, returns false if it did not apply
+    fn apply(&self, i: usize, assignments: &mut Assignments, mut rng: &mut dyn RngCore) -> bool {
+        match MOVES[i] {
+            "rl" => assignments.randomize_left(&mut rng),
+            "rr" => assignments.randomize_right(&mut rng),
+            "sl" => assignments.random_swaps_of_l_forbidden(&mut rng),
+            "sr" => assignments.random_swaps_of_r_forbidden(&mut rng),
+            "bsl" => {
+                assignments.f_min_backup_restore();
+                assignments.random_swaps_of_l_forbidden(&mut rng);
+            }
+            "bsr" => {
+                assignments.f_min_backup_restore();
+                assignments.random_swaps_of_r_forbidden(&mut rng);
+            }
+            _ => {
+                // "sbrk"
+                let tmp = assignments.get_pairs_of_ids();
+                let Ok(pairs) = assignments.try_switching_endpoints(tmp.clone()) else {
+                    return false;
+                };
+                assignments.set_pairs_of_ids(&pairs);
+                if assignments.test_assignments().is_err() {
+                    assignments.set_pairs_of_ids(&tmp);
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl Default for Portfolio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SearchStrategy for Portfolio {
+    fn name(&self) -> &str {
+        "portfolio"
+    }
+
+    fn description(&self) -> &str {
+        "moves of rl, rr, sl, sr, bsl, bsr, sbrk by weights (w_<move>) or adaptive bandit, restarts when stuck"
+    }
+
+    fn parameters(&self) -> Vec<(String, String)> {
+        let mut out = vec![
+            (
+                String::from("mode"),
+                String::from(if self.bandit { "bandit" } else { "weights" }),
+            ),
+            (String::from("epsilon"), self.epsilon.to_string()),
+            (String::from("decay"), self.decay.to_string()),
+            (String::from("stall"), self.stall.to_string()),
+        ];
+        for (name, w) in MOVES.iter().zip(&self.weights) {
+            out.push((format!("w_{name}"), w.to_string()));
+        }
+        out
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        let probability = |value: &str| -> Result<f64, Box<dyn Error>> {
+            let x: f64 = value.parse()?;
+            if !(0.0..=1.0).contains(&x) {
+                return Err(format!("{x} is not in [0, 1] !!!").into());
+            }
+            Ok(x)
+        };
+        match name {
+            "mode" => match value {
+                "bandit" => self.bandit = true,
+                "weights" => self.bandit = false,
+                _ => return Err(format!("mode `{value}` is not one of bandit, weights !!!").into()),
+            },
+            "epsilon" => self.epsilon = probability(value)?,
+            "decay" => self.decay = probability(value)?,
+            "stall" => self.stall = value.parse()?,
+            _ => {
+                let Some(i) = name
+                    .strip_prefix("w_")
+                    .and_then(|x| MOVES.iter().position(|m| *m == x))
+                else {
+                    return Err(format!("unknown parameter `{name}` !!!").into());
+                };
+                let w: f64 = value.parse()?;
+                if !(w >= 0.0 && w.is_finite()) {
+                    return Err(format!("weight {w} is not a finite number >= 0 !!!").into());
+                }
+                self.weights[i] = w;
+            }
+        }
+        Ok(())
+    }
+
+    fn init(
+        &mut self,
+        assignments: &mut Assignments,
+        _rng: &mut dyn RngCore,
+    ) -> Result<(), Box<dyn Error>> {
+        // `weights` is public: check it here, not only in `set_parameter`
+        if self.weights.len() != MOVES.len() {
+            return Err(format!(
+                "{} weights for {} moves !!!",
+                self.weights.len(),
+                MOVES.len()
+            )
+            .into());
+        }
+        if let Some(w) = self.weights.iter().find(|w| !(**w >= 0.0 && w.is_finite())) {
+            return Err(format!("weight {w} is not a finite number >= 0 !!!").into());
+        }
+        let sum = self.weights.iter().sum::<f64>();
+        if sum <= 0.0 {
+            return Err("all weights of moves are 0 !!!".into());
+        } else if !sum.is_finite() {
+            return Err(format!("the sum of the weights of moves {sum} is not finite !!!").into());
+        }
+        self.best = assignments.number_of_forbidden_used();
+        self.since_best = 0;
+        Ok(())
+    }
+
+    fn step(
+        &mut self,
+        assignments: &mut Assignments,
+        mut rng: &mut dyn RngCore,
+    ) -> Result<(), Box<dyn Error>> {
+        // random restart when stuck
+        if self.stall > 0 && self.since_best >= self.stall {
+            assignments.randomize_left(&mut rng);
+            assignments.randomize_right(&mut rng);
+            self.restarts += 1;
+            self.since_best = 0;
+            self.last_move = "restart";
+            return Ok(());
+        }
+        let i = self.choose(rng);
+        self.last_move = MOVES[i];
+        let before = assignments.number_of_forbidden_used();
+        let applied = self.apply(i, assignments, rng);
+        let after = assignments.number_of_forbidden_used();
+        let stats = &mut self.stats[i];
+        stats.used += 1;
+        if !applied {
+            stats.failed += 1;
+        }
+        let improved = after < before;
+        if improved {
+            stats.improved += 1;
+        }
+        if after < self.best {
+            self.best = after;
+            self.since_best = 0;
+            stats.best += 1;
+        } else {
+            self.since_best += 1;
+        }
+        let reward = if improved { 1.0 } else { 0.0 };
+        self.scores[i] = self.decay * self.scores[i] + (1.0 - self.decay) * reward;
+        Ok(())
+    }
+
+    fn last_move(&self) -> &str {
+        self.last_move
+    }
+
+    fn report(&self) -> String {
+        let mut out = format!(
+            "  move   used  improved  new best  failed  score  (restarts: {})\n",
+            self.restarts
+        );
+        for (i, name) in MOVES.iter().enumerate() {
+            let s = &self.stats[i];
+            out = format!(
+                "{}  {:<5} {:>6} {:>9} {:>9} {:>7}  {:.3}\n",
+                out, name, s.used, s.improved, s.best, s.failed, self.scores[i]
+            );
+        }
+        out
+    }
+}
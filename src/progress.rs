@@ -0,0 +1,213 @@
+// Progress of searches: periodic reports, time budget and interruption (Ctrl-C)
+
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+// default interval of `SearchProgress::on_report`
+pub const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+static SEARCHING: AtomicBool = AtomicBool::new(false);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// `install_interrupt_handler` - Ctrl-C interrupts a running search (see `interrupted`)
+// and terminates the program otherwise
+pub fn install_interrupt_handler() -> Result<(), Box<dyn Error>> {
+    ctrlc::set_handler(|| {
+        if SEARCHING.load(Ordering::SeqCst) {
+            INTERRUPTED.store(true, Ordering::SeqCst);
+        } else {
+            std::process::exit(130);
+        }
+    })?;
+    Ok(())
+}
+
+// `interrupted` is true after Ctrl-C during the running search
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+// `SearchGuard` marks a running search (Ctrl-C interrupts it) until dropped
+pub struct SearchGuard();
+
+impl SearchGuard {
+    pub fn new() -> Self {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        SEARCHING.store(true, Ordering::SeqCst);
+        Self()
+    }
+}
+
+impl Default for SearchGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SearchGuard {
+    fn drop(&mut self) {
+        SEARCHING.store(false, Ordering::SeqCst);
+        INTERRUPTED.store(false, Ordering::SeqCst);
+    }
+}
+
+// Budget - limits of a search: `max` steps and optional wall-clock `time`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Budget {
+    pub max: usize,
+    pub time: Option<Duration>,
+}
+
+impl Budget {
+    pub fn steps(max: usize) -> Self {
+        Self { max, time: None }
+    }
+}
+
+// Stop - why a search stopped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stop {
+    Solved,      // zero forbidden pairs in assignments
+    Bound,       // forbidden pairs reached their minimum
+    Steps,       // `max` steps done
+    Time,        // time budget exhausted
+    Interrupted, // Ctrl-C
+    Requested,   // `SearchProgress::should_stop`
+    Failed,      // a step of the strategy failed
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Stop::Solved => "zero forbidden found",
+            Stop::Bound => "minimum of forbidden reached",
+            Stop::Steps => "max steps done",
+            Stop::Time => "time budget exhausted",
+            Stop::Interrupted => "interrupted",
+            Stop::Requested => "stopped on request",
+            Stop::Failed => "failed",
+        };
+        write!(f, "{reason}")
+    }
+}
+
+// Progress - state of a running search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub steps: usize,
+    pub forbidden: usize, // forbidden pairs in the current assignments
+    pub best: usize,      // forbidden pairs in the best assignments found
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    pub fn steps_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.steps as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} steps, forbidden {} (best {}), {:.1}s, {:.0} steps/s",
+            self.steps,
+            self.forbidden,
+            self.best,
+            self.elapsed.as_secs_f64(),
+            self.steps_per_sec()
+        )
+    }
+}
+
+// SearchProgress - callbacks of running searches (for rendering progress by embedding code)
+pub trait SearchProgress {
+    // called once before the first step
+    fn on_start(&mut self, _progress: &Progress) {}
+
+    // called after each step with the name of the move done (see `SearchStrategy::last_move`)
+    fn on_step(&mut self, _progress: &Progress, _movement: &str) {}
+
+    // called every `report_interval`
+    fn on_report(&mut self, _progress: &Progress) {}
+
+    // called after each step, returns true to stop the search
+    fn should_stop(&mut self, _progress: &Progress) -> bool {
+        false
+    }
+
+    // called once when the search stops
+    fn on_stop(&mut self, _progress: &Progress, _stop: Stop) {}
+
+    fn report_interval(&self) -> Duration {
+        REPORT_INTERVAL
+    }
+}
+
+// NoProgress - silent searches
+pub struct NoProgress();
+
+impl SearchProgress for NoProgress {}
+
+// PrintProgress - prints reports (and unusual stops) prefixed with `cmd`
+pub struct PrintProgress {
+    pub cmd: String,
+    pub to_stderr: bool,
+}
+
+impl PrintProgress {
+    fn print(&self, line: String) {
+        if self.to_stderr {
+            eprintln!("{}: {line}", self.cmd);
+        } else {
+            println!("{}: {line}", self.cmd);
+        }
+    }
+}
+
+impl SearchProgress for PrintProgress {
+    fn on_report(&mut self, progress: &Progress) {
+        self.print(format!("... {progress}"));
+    }
+
+    fn on_stop(&mut self, progress: &Progress, stop: Stop) {
+        if stop == Stop::Bound {
+            self.print(format!("{stop} after {progress}."));
+        } else if matches!(
+            stop,
+            Stop::Time | Stop::Interrupted | Stop::Requested | Stop::Failed
+        ) {
+            self.print(format!(
+                "{stop} after {progress}, the best assignments restored."
+            ));
+        }
+    }
+}
+
+// `parse_duration` parses e.g. `30s`, `500ms`, `2m`, `1h` or `30` (seconds)
+pub fn parse_duration(input: &str) -> Result<Duration, Box<dyn Error>> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration `{input}` (e.g. 30s, 500ms, 2m, 1h) !!!"))?;
+    let secs = match unit {
+        "" | "s" => number,
+        "ms" => number / 1000.0,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        _ => return Err(format!("invalid duration unit in `{input}` (ms, s, m or h) !!!").into()),
+    };
+    Duration::try_from_secs_f64(secs)
+        .map_err(|err| format!("invalid duration `{input}`: {err} !!!").into())
+}
@@ -0,0 +1,45 @@
+// Minimal-disruption repair: valid assignments changing as few pairs of the current ones as possible
+// (e.g. after a new conflict is added to `forbidden`)
+
+use crate::diff::*;
+use crate::*;
+
+// `repair_pairs` - pairs with the fewest forbidden pairs and then the fewest pairs not in `baseline`
+// (by min-cost flow: a kept pair costs 0, a new pair 1, a forbidden pair more than all the new ones)
+pub fn repair_pairs(
+    k: usize,
+    m: usize,
+    n: usize,
+    p: usize,
+    baseline: &[(usize, usize)],
+    forbidden: &[(usize, usize)],
+) -> Option<Vec<(usize, usize)>> {
+    // pairs outside m x n (e.g. not remapped by `resized`) are in no assignments of (k, m, n)
+    let in_range = |&&(l, r): &&(usize, usize)| l < m && r < n;
+    let mut cost = vec![vec![1; n]; m];
+    for &(l, r) in baseline.iter().filter(in_range) {
+        cost[l][r] = 0;
+    }
+    let forbidden_cost = (m * p + 1) as i64;
+    for &(l, r) in forbidden.iter().filter(in_range) {
+        cost[l][r] = forbidden_cost;
+    }
+    let (_, mut pairs) = flow::min_cost_pairs(k, m, n, p, |l, r| Some(cost[l][r]))?;
+    pairs.sort();
+    Some(pairs)
+}
+
+// Repair - repaired pairs with the changes from the current ones
+pub type Repair = (Vec<(usize, usize)>, AssignmentsDiff);
+
+impl Assignments {
+    // `repaired` - the repaired pairs and the changes from the current ones (see `repair_pairs`)
+    pub fn repaired(&self) -> Result<Repair, Box<dyn Error>> {
+        let (k, m, n) = self.get_kmn();
+        let baseline = self.get_pairs_of_ids();
+        let pairs = repair_pairs(k, m, n, self.p(), &baseline, &self.forbidden)
+            .ok_or("no assignments satisfy the degree rules !!!")?;
+        let diff = AssignmentsDiff::of(&baseline, &self.forbidden, &pairs, &self.forbidden);
+        Ok((pairs, diff))
+    }
+}
@@ -0,0 +1,219 @@
+// Replay of recorded sessions: compare JSON results and transcripts with the expected ones
+
+use serde_json::Value;
+use std::fmt;
+
+// `JsonDiff` - one difference between the expected and the actual JSON at `path`
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonDiff {
+    pub path: String,            // e.g. `$.assignments[3][1]`
+    pub expected: Option<Value>, // `None` - missing in expected
+    pub actual: Option<Value>,   // `None` - missing in actual
+}
+
+impl fmt::Display for JsonDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |v: &Option<Value>| match v {
+            Some(v) => v.to_string(),
+            None => String::from("(missing)"),
+        };
+        write!(
+            f,
+            "{}: expected {}, actual {}",
+            self.path,
+            show(&self.expected),
+            show(&self.actual)
+        )
+    }
+}
+
+// `json_diff` collects to `out` the differences between `expected` and `actual` below `path`
+pub fn json_diff(path: &str, expected: &Value, actual: &Value, out: &mut Vec<JsonDiff>) {
+    match (expected, actual) {
+        (Value::Object(e), Value::Object(a)) => {
+            for (key, e_value) in e {
+                let key_path = format!("{path}.{key}");
+                match a.get(key) {
+                    Some(a_value) => json_diff(&key_path, e_value, a_value, out),
+                    None => out.push(JsonDiff {
+                        path: key_path,
+                        expected: Some(e_value.clone()),
+                        actual: None,
+                    }),
+                }
+            }
+            for (key, a_value) in a {
+                if !e.contains_key(key) {
+                    out.push(JsonDiff {
+                        path: format!("{path}.{key}"),
+                        expected: None,
+                        actual: Some(a_value.clone()),
+                    });
+                }
+            }
+        }
+        (Value::Array(e), Value::Array(a)) if is_pair_list(e) && is_pair_list(a) => {
+            // lists of (l, r) pairs are compared as sets
+            let only_in = |x: &Vec<Value>, y: &Vec<Value>| -> Vec<Value> {
+                x.iter().filter(|p| !y.contains(p)).cloned().collect()
+            };
+            let (missing, extra) = (only_in(e, a), only_in(a, e));
+            if !missing.is_empty() || !extra.is_empty() {
+                out.push(JsonDiff {
+                    path: format!("{path} (as set of pairs)"),
+                    expected: Some(Value::Array(missing)),
+                    actual: Some(Value::Array(extra)),
+                });
+            }
+        }
+        (Value::Array(e), Value::Array(a)) => {
+            for i in 0..e.len().max(a.len()) {
+                let index_path = format!("{path}[{i}]");
+                match (e.get(i), a.get(i)) {
+                    (Some(e_value), Some(a_value)) => json_diff(&index_path, e_value, a_value, out),
+                    (e_value, a_value) => out.push(JsonDiff {
+                        path: index_path,
+                        expected: e_value.cloned(),
+                        actual: a_value.cloned(),
+                    }),
+                }
+            }
+        }
+        _ => {
+            if expected != actual {
+                out.push(JsonDiff {
+                    path: path.to_string(),
+                    expected: Some(expected.clone()),
+                    actual: Some(actual.clone()),
+                });
+            }
+        }
+    }
+}
+
+// `is_pair_list` - a non-empty list of pairs of numbers like `assignments` or `forbidden`
+fn is_pair_list(values: &[Value]) -> bool {
+    !values.is_empty()
+        && values.iter().all(|v| match v {
+            Value::Array(pair) => pair.len() == 2 && pair.iter().all(|x| x.is_u64()),
+            _ => false,
+        })
+}
+
+// `Printed` - assignments and forbidden pairs as printed by the menu command `p`
+// (e.g. `test-data/test-data-3-10-19-t1-solution`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Printed {
+    pub kmn: (usize, usize, usize),
+    pub pairs: Vec<(usize, usize)>,
+    pub forbidden: Vec<(usize, usize)>,
+}
+
+impl Printed {
+    // `parse` the `Assignments (k,m,n) = (K, M, N) ...` and `Forbidden (...)` headers with their `[ l r ... ]` lists
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut out = Printed::default();
+        let mut kmn = None;
+        let mut list: Option<&mut Vec<(usize, usize)>> = None;
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if let Some(rest) = line.strip_prefix("Assignments (k,m,n) = (") {
+                let numbers: Vec<usize> = rest
+                    .split(')')
+                    .next()
+                    .unwrap_or_default()
+                    .split(',')
+                    .filter_map(|x| x.trim().parse().ok())
+                    .collect();
+                let [k, m, n] = numbers[..] else {
+                    return Err(format!("line {}: bad (k,m,n) !!!", i + 1));
+                };
+                kmn = Some((k, m, n));
+                list = Some(&mut out.pairs);
+            } else if line.starts_with("Forbidden (") {
+                list = Some(&mut out.forbidden);
+            } else if line == "[" || line == "]" || line.is_empty() {
+                continue;
+            } else if let Some(pairs) = list.as_mut() {
+                let words: Vec<&str> = line.split_whitespace().collect();
+                match words[..] {
+                    [l, r] | [l, r, "!!!"] => match (l.parse(), r.parse()) {
+                        (Ok(l), Ok(r)) => pairs.push((l, r)),
+                        _ => return Err(format!("line {}: bad pair `{line}` !!!", i + 1)),
+                    },
+                    _ => return Err(format!("line {}: bad pair `{line}` !!!", i + 1)),
+                }
+            }
+        }
+        out.kmn = kmn.ok_or("no `Assignments (k,m,n) = ...` header !!!")?;
+        Ok(out)
+    }
+}
+
+// `TranscriptDiff` - the first different line of the transcripts
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptDiff {
+    pub line: usize, // line number (from 1)
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+impl fmt::Display for TranscriptDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "transcript differs at line {}:", self.line)?;
+        writeln!(f, "  expected: {:?}", self.expected)?;
+        write!(f, "  actual:   {:?}", self.actual)
+    }
+}
+
+// `transcript_diff` returns the first different line (ignoring trailing white space)
+pub fn transcript_diff(expected: &str, actual: &str) -> Option<TranscriptDiff> {
+    let mut e_lines = expected.lines().map(|x| x.trim_end());
+    let mut a_lines = actual.lines().map(|x| x.trim_end());
+    let mut line = 0;
+    loop {
+        line += 1;
+        match (e_lines.next(), a_lines.next()) {
+            (None, None) => return None,
+            (e, a) if e == a => continue,
+            (e, a) => {
+                return Some(TranscriptDiff {
+                    line,
+                    expected: e.map(String::from),
+                    actual: a.map(String::from),
+                });
+            }
+        }
+    }
+}
+
+// `Replay` - result of comparing a replayed session with the expected files
+#[derive(Debug, Default)]
+pub struct Replay {
+    pub json_diffs: Vec<JsonDiff>,
+    pub transcript_diff: Option<TranscriptDiff>,
+}
+
+impl Replay {
+    pub fn passed(&self) -> bool {
+        self.json_diffs.is_empty() && self.transcript_diff.is_none()
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.passed() {
+            return write!(f, "replay: results as expected.");
+        }
+        if !self.json_diffs.is_empty() {
+            writeln!(f, "replay: {} JSON difference(s):", self.json_diffs.len())?;
+            for diff in &self.json_diffs {
+                writeln!(f, "  {diff}")?;
+            }
+        }
+        if let Some(diff) = &self.transcript_diff {
+            writeln!(f, "{diff}")?;
+        }
+        write!(f, "replay: results differ !!!")
+    }
+}
@@ -0,0 +1,192 @@
+// Adding and removing lefts and rights of existing assignments: the kept ids are remapped in their order,
+// the new ones follow, and the assignments restoring the degree rules keep as many existing pairs as possible
+
+use crate::repair::*;
+use crate::*;
+
+// Resize - how the ids and the pairs were carried over
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Resize {
+    pub left_map: Vec<Option<usize>>, // old left id -> new left id (None if removed)
+    pub right_map: Vec<Option<usize>>, // old right id -> new right id (None if removed)
+    pub kept: usize,                  // old pairs kept (remapped)
+    pub dropped: usize,               // old pairs not kept
+    pub added: usize,                 // new pairs
+}
+
+impl fmt::Display for Resize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} pairs kept, {} dropped, {} added",
+            self.kept, self.dropped, self.added
+        )
+    }
+}
+
+impl Resize {
+    // `unaffected_lefts` - (old id, new id) of the lefts whose new rights are their remapped old ones
+    // (e.g. rankers keeping their rankings)
+    pub fn unaffected_lefts(&self, old: &Assignments, new: &Assignments) -> Vec<(usize, usize)> {
+        let rights_of = |pairs: Vec<(usize, usize)>, m: usize| {
+            let mut out: Vec<Vec<usize>> = vec![vec![]; m];
+            for (l, r) in pairs {
+                out[l].push(r);
+            }
+            out
+        };
+        let old_rights = rights_of(old.get_pairs_of_ids(), old.m);
+        let mut new_rights = rights_of(new.get_pairs_of_ids(), new.m);
+        let mut out = vec![];
+        for (l, rights) in old_rights.iter().enumerate() {
+            let Some(l1) = self.left_map[l] else {
+                continue;
+            };
+            let mut mapped: Vec<Option<usize>> =
+                rights.iter().map(|r| self.right_map[*r]).collect();
+            mapped.sort();
+            new_rights[l1].sort();
+            if mapped
+                .iter()
+                .copied()
+                .eq(new_rights[l1].iter().map(|r| Some(*r)))
+            {
+                out.push((l, l1));
+            }
+        }
+        out
+    }
+}
+
+// `kept_labels` - labels of the kept `ids` of a side with `len` ids (none if some ids have none)
+fn kept_labels(
+    side: &str,
+    labels: &[String],
+    len: usize,
+    ids: &[usize],
+    new: usize,
+) -> Vec<String> {
+    if labels.len() != len {
+        return vec![];
+    }
+    if new > 0 {
+        eprintln!("#WARNING# resized: labels of the {side} dropped (the new {side} have none) !!!");
+        return vec![];
+    }
+    ids.iter().map(|&id| labels[id].clone()).collect()
+}
+
+impl Assignments {
+    // `resized` - assignments of the kept `left_ids` and `right_ids` (remapped to 0, 1, ... in sorted order)
+    // followed by `new_lefts` and `new_rights` new ids, with `k` (the current one if None), the forbidden
+    // pairs remapped and as many old pairs as possible (see `repair::repair_pairs`)
+    pub fn resized(
+        &self,
+        left_ids: &[usize],
+        new_lefts: usize,
+        right_ids: &[usize],
+        new_rights: usize,
+        k: Option<usize>,
+    ) -> Result<(Assignments, Resize), Box<dyn Error>> {
+        let (mut left_ids, mut right_ids) = (left_ids.to_vec(), right_ids.to_vec());
+        let left_map = self.sort_and_test_subset_of_left_ids(&mut left_ids)?;
+        let right_map = self.sort_and_test_subset_of_right_ids(&mut right_ids)?;
+        let (m, n) = (left_ids.len() + new_lefts, right_ids.len() + new_rights);
+        let k = k.unwrap_or(self.k);
+        if !(1 <= k && k <= m) {
+            return Err(format!(
+                "resized: 1 <= k <= m is not satisfied for (k, m, n) = ({k}, {m}, {n}) !!!"
+            )
+            .into());
+        }
+        // checked before `Assignments::new`, which panics on them
+        if n == 0 {
+            return Err(
+                format!("resized: no rights left for (k, m, n) = ({k}, {m}, {n}) !!!").into(),
+            );
+        }
+        let p = div_ceil(k * n, m);
+        if p > n {
+            return Err(
+                format!("resized: p = {p} > n = {n} for (k, m, n) = ({k}, {m}, {n}) !!!").into(),
+            );
+        }
+        let mut out = Assignments::new(k, m, n);
+        let remapped = |pairs: &Vec<(usize, usize)>| -> Vec<(usize, usize)> {
+            pairs
+                .iter()
+                .filter_map(|(l, r)| Some((left_map[*l]?, right_map[*r]?)))
+                .collect()
+        };
+        let baseline = remapped(&self.get_pairs_of_ids());
+        out.forbidden = remapped(&self.forbidden);
+        out.set_labels(Labels {
+            lefts: kept_labels("lefts", &self.labels().lefts, self.m, &left_ids, new_lefts),
+            rights: kept_labels(
+                "rights",
+                &self.labels().rights,
+                self.n,
+                &right_ids,
+                new_rights,
+            ),
+        });
+        let pairs = repair_pairs(k, m, n, p, &baseline, &out.forbidden)
+            .ok_or("resized: no assignments satisfy the degree rules !!!")?;
+        let kept = pairs.iter().filter(|x| baseline.contains(x)).count();
+        let resize = Resize {
+            left_map,
+            right_map,
+            kept,
+            dropped: self.get_pairs_of_ids().len() - kept,
+            added: pairs.len() - kept,
+        };
+        out.set_pairs_of_ids(&pairs);
+        Ok((out, resize))
+    }
+
+    // `without_lefts` - the assignments without the lefts `ids`
+    pub fn without_lefts(
+        &self,
+        ids: &[usize],
+        k: Option<usize>,
+    ) -> Result<(Assignments, Resize), Box<dyn Error>> {
+        if let Some(id) = ids.iter().find(|x| **x >= self.m) {
+            return Err(format!("without_lefts: {id} >= m = {} !!!", self.m).into());
+        }
+        let kept: Vec<usize> = (0..self.m).filter(|l| !ids.contains(l)).collect();
+        self.resized(&kept, 0, &(0..self.n).collect::<Vec<_>>(), 0, k)
+    }
+
+    // `without_rights` - the assignments without the rights `ids`
+    pub fn without_rights(
+        &self,
+        ids: &[usize],
+        k: Option<usize>,
+    ) -> Result<(Assignments, Resize), Box<dyn Error>> {
+        if let Some(id) = ids.iter().find(|x| **x >= self.n) {
+            return Err(format!("without_rights: {id} >= n = {} !!!", self.n).into());
+        }
+        let kept: Vec<usize> = (0..self.n).filter(|r| !ids.contains(r)).collect();
+        self.resized(&(0..self.m).collect::<Vec<_>>(), 0, &kept, 0, k)
+    }
+
+    // `with_new_lefts` - the assignments with `count` new lefts (ids m, m+1, ...)
+    pub fn with_new_lefts(
+        &self,
+        count: usize,
+        k: Option<usize>,
+    ) -> Result<(Assignments, Resize), Box<dyn Error>> {
+        let all = |len: usize| (0..len).collect::<Vec<_>>();
+        self.resized(&all(self.m), count, &all(self.n), 0, k)
+    }
+
+    // `with_new_rights` - the assignments with `count` new rights (ids n, n+1, ...)
+    pub fn with_new_rights(
+        &self,
+        count: usize,
+        k: Option<usize>,
+    ) -> Result<(Assignments, Resize), Box<dyn Error>> {
+        let all = |len: usize| (0..len).collect::<Vec<_>>();
+        self.resized(&all(self.m), 0, &all(self.n), count, k)
+    }
+}
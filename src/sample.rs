@@ -0,0 +1,105 @@
+// Uniform random sampling of valid assignments (all the bipartite graphs with the required degrees
+// avoiding `forbidden`, not only the relabelings of one structure) by a Markov chain of switches and shifts
+
+use crate::*;
+
+// default mixing length: steps of the chain for each pair of assignments
+pub const MIXING_PER_PAIR: usize = 100;
+
+// SampleStats - steps of the chain and the accepted moves
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SampleStats {
+    pub steps: usize,
+    pub switches: usize, // (l1, r1), (l2, r2) -> (l1, r2), (l2, r1)
+    pub shifts: usize,   // (l, r1) -> (l, r2) moving the extra degree k+1 from r1 to r2
+}
+
+impl fmt::Display for SampleStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} steps, {} switches and {} shifts accepted",
+            self.steps, self.switches, self.shifts
+        )
+    }
+}
+
+// Sample - sampled pairs with the statistics of the chain
+pub type Sample = (Vec<(usize, usize)>, SampleStats);
+
+// `sample_uniform` - random valid assignments after `steps` steps of the chain starting from
+// the current assignments (or from min-cost flow ones if the current contain forbidden pairs)
+//
+// Each step proposes with probability 1/2 a switch of two random pairs and otherwise a shift
+// of a random pair to a random right; proposals breaking the degrees or hitting `forbidden`
+// are rejected. The proposals are symmetric, so the chain converges to the uniform distribution
+// (on the assignments reachable by the moves).
+pub fn sample_uniform(
+    assignments: &Assignments,
+    steps: usize,
+    rng: &mut impl Rng,
+) -> Result<Sample, Box<dyn Error>> {
+    let (k, m, n) = assignments.get_kmn();
+    let mut is_forbidden = vec![vec![false; n]; m];
+    for &(l, r) in assignments.forbidden() {
+        if l >= m || r >= n {
+            return Err(format!("forbidden ({l}, {r}) out of range !!!").into());
+        }
+        is_forbidden[l][r] = true;
+    }
+    let mut pairs = assignments.get_pairs_of_ids();
+    if assignments.number_of_forbidden_used() > 0 {
+        let cost = |l: usize, r: usize| (!is_forbidden[l][r]).then_some(0);
+        let Some((_, valid)) = flow::min_cost_pairs(k, m, n, assignments.p(), cost) else {
+            return Err("no valid assignments avoid the forbidden pairs !!!".into());
+        };
+        pairs = valid;
+    }
+    let mut used = vec![vec![false; n]; m];
+    let mut r_deg = vec![0; n];
+    for &(l, r) in &pairs {
+        used[l][r] = true;
+        r_deg[r] += 1;
+    }
+    let mut stats = SampleStats {
+        steps,
+        ..Default::default()
+    };
+    for _ in 0..steps {
+        let i = rng.random_range(0..pairs.len());
+        let (l1, r1) = pairs[i];
+        if rng.random_bool(0.5) {
+            let j = rng.random_range(0..pairs.len());
+            let (l2, r2) = pairs[j];
+            if l1 == l2
+                || r1 == r2
+                || used[l1][r2]
+                || used[l2][r1]
+                || is_forbidden[l1][r2]
+                || is_forbidden[l2][r1]
+            {
+                continue;
+            }
+            used[l1][r1] = false;
+            used[l2][r2] = false;
+            used[l1][r2] = true;
+            used[l2][r1] = true;
+            pairs[i] = (l1, r2);
+            pairs[j] = (l2, r1);
+            stats.switches += 1;
+        } else {
+            let r2 = rng.random_range(0..n);
+            if r_deg[r1] != k + 1 || r_deg[r2] != k || used[l1][r2] || is_forbidden[l1][r2] {
+                continue;
+            }
+            used[l1][r1] = false;
+            used[l1][r2] = true;
+            r_deg[r1] -= 1;
+            r_deg[r2] += 1;
+            pairs[i] = (l1, r2);
+            stats.shifts += 1;
+        }
+    }
+    pairs.sort();
+    Ok((pairs, stats))
+}
@@ -0,0 +1,206 @@
+// Skeleton families: the structures of the default assignments (all with left degrees p
+// and right degrees k or k+1)
+
+use crate::cover::*;
+use crate::cp::*;
+use crate::repair::*;
+use crate::sample::*;
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+// max number of nodes of the CP search of `SkeletonKind::MinOverlap`
+pub const MIN_OVERLAP_NODES: usize = 20_000;
+
+// SkeletonKind
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkeletonKind {
+    #[default]
+    Cyclic, // `Pairs::kmn_pairs`: the lefts take the rights in turn
+    BlockCirculant,  // left l takes the p consecutive rights from l*p (mod n)
+    RandomBiregular, // uniformly random (see `sample::sample_uniform`)
+    MinOverlap,      // minimal max number of rights shared by two lefts (by `cp::CpSolver`)
+    Covering, // as many pairs of rights as possible share a left (by `cover::improve_coverage`)
+    MaxGirth, // the shortest cycles as long as progressive edge growth finds (`max_girth_pairs`)
+}
+
+pub const SKELETON_KINDS: [SkeletonKind; 6] = [
+    SkeletonKind::Cyclic,
+    SkeletonKind::BlockCirculant,
+    SkeletonKind::RandomBiregular,
+    SkeletonKind::MinOverlap,
+    SkeletonKind::Covering,
+    SkeletonKind::MaxGirth,
+];
+
+impl SkeletonKind {
+    pub fn is_cyclic(&self) -> bool {
+        *self == SkeletonKind::Cyclic
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SkeletonKind::Cyclic => "cyclic",
+            SkeletonKind::BlockCirculant => "block-circulant",
+            SkeletonKind::RandomBiregular => "random-biregular",
+            SkeletonKind::MinOverlap => "min-overlap",
+            SkeletonKind::Covering => "covering",
+            SkeletonKind::MaxGirth => "max-girth",
+        }
+    }
+
+    // names of all the kinds separated by `, `
+    pub fn names() -> String {
+        SKELETON_KINDS.map(|x| x.name()).join(", ")
+    }
+}
+
+impl fmt::Display for SkeletonKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for SkeletonKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SKELETON_KINDS
+            .into_iter()
+            .find(|x| x.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "unknown skeleton `{s}` (skeletons: {}) !!!",
+                    SkeletonKind::names()
+                )
+            })
+    }
+}
+
+// `block_circulant_pairs` - left l takes the rights l*p, ..., l*p + p - 1 (mod n),
+// so the m*p pairs wrap around the rights evenly (k or k+1 times)
+fn block_circulant_pairs(m: usize, n: usize, p: usize) -> Vec<(usize, usize)> {
+    let mut out = vec![];
+    for l in 0..m {
+        for j in 0..p {
+            out.push((l, (l * p + j) % n));
+        }
+    }
+    out
+}
+
+// `max_girth_pairs` - progressive edge growth: each new pair of left l takes a right farthest
+// from l in the pairs so far (unreachable first, then the lowest overlap of l with the lefts of
+// the right, then the fewest lefts, then the lowest id), so the cycles it closes are as long and
+// as few as possible; the pairs are then fitted to the degree
+// rules by `repair_pairs` (keeping as many of them as possible)
+fn max_girth_pairs(k: usize, m: usize, n: usize, p: usize) -> Option<Vec<(usize, usize)>> {
+    let extra = (m * p).saturating_sub(k * n); // rights with k+1 lefts
+    let mut lefts_of: Vec<Vec<usize>> = vec![vec![]; n];
+    let mut rights_of: Vec<Vec<usize>> = vec![vec![]; m];
+    let mut full = 0; // rights with k+1 lefts so far
+    for l in 0..m {
+        for _ in 0..p {
+            // distances (in pairs) of the rights from l
+            let mut r_dist = vec![usize::MAX; n];
+            let mut l_seen = vec![false; m];
+            l_seen[l] = true;
+            let mut frontier = vec![l];
+            let mut dist = 1;
+            while !frontier.is_empty() {
+                let mut next = vec![];
+                for &l1 in &frontier {
+                    for &r in &rights_of[l1] {
+                        if r_dist[r] == usize::MAX {
+                            r_dist[r] = dist;
+                            for &l2 in &lefts_of[r] {
+                                if !l_seen[l2] {
+                                    l_seen[l2] = true;
+                                    next.push(l2);
+                                }
+                            }
+                        }
+                    }
+                }
+                frontier = next;
+                dist += 2;
+            }
+            // rights shared by l with each other left
+            let mut shared = vec![0; m];
+            for &r in &rights_of[l] {
+                for &l2 in &lefts_of[r] {
+                    shared[l2] += 1;
+                }
+            }
+            let overlap = |r: usize| lefts_of[r].iter().map(|&l2| shared[l2]).max().unwrap_or(0);
+            let open = |r: usize| {
+                let deg = lefts_of[r].len();
+                r_dist[r] != 1 && (deg < k || (deg == k && full < extra))
+            };
+            let Some(r) = (0..n)
+                .filter(|&r| open(r))
+                .min_by_key(|&r| (usize::MAX - r_dist[r], overlap(r), lefts_of[r].len(), r))
+            else {
+                break; // no right left for l: `repair_pairs` completes it
+            };
+            if lefts_of[r].len() == k {
+                full += 1;
+            }
+            lefts_of[r].push(l);
+            rights_of[l].push(r);
+        }
+    }
+    let pairs: Vec<(usize, usize)> = (0..m)
+        .flat_map(|l| rights_of[l].iter().map(move |&r| (l, r)))
+        .collect();
+    repair_pairs(k, m, n, p, &pairs, &[])
+}
+
+impl Assignments {
+    // `with_skeleton` - default assignments of (k, m, n) with the skeleton of `kind`; the cyclic
+    // skeleton (recorded as such, see `skeleton`) if the generator of `kind` finds nothing
+    pub fn with_skeleton(
+        kind: SkeletonKind,
+        k: usize,
+        m: usize,
+        n: usize,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let mut out = Self::new(k, m, n);
+        let p = out.p();
+        let pairs = match kind {
+            SkeletonKind::Cyclic => None,
+            SkeletonKind::BlockCirculant => Some(block_circulant_pairs(m, n, p)),
+            SkeletonKind::RandomBiregular => {
+                let steps = MIXING_PER_PAIR * m * p;
+                sample_uniform(&out, steps, rng)
+                    .ok()
+                    .map(|(pairs, _)| pairs)
+            }
+            SkeletonKind::MinOverlap => {
+                let solver = CpSolver {
+                    node_limit: MIN_OVERLAP_NODES,
+                    ..Default::default()
+                };
+                solver.solve(&out).ok().and_then(|outcome| outcome.pairs)
+            }
+            SkeletonKind::Covering => {
+                let steps = COVER_STEPS_PER_PAIR * m * p;
+                improve_coverage(&out, steps, rng)
+                    .ok()
+                    .map(|(pairs, _)| pairs)
+            }
+            SkeletonKind::MaxGirth => max_girth_pairs(k, m, n, p),
+        };
+        if let Some(pairs) = pairs {
+            out.set_pairs_of_ids(&pairs);
+            out.skeleton = kind;
+        }
+        out
+    }
+
+    pub fn skeleton(&self) -> SkeletonKind {
+        self.skeleton
+    }
+}
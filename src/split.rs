@@ -0,0 +1,397 @@
+// Splitting large events into tracks: the lefts and the rights are partitioned (by explicit lists of ids
+// or labels, or by clustering of the allowed-pair graph), each track is reduced to its own assignments
+// (see `Assignments::reduced_to`), solved, and the results are mapped back to the original ids
+
+use crate::kmn_serde::*;
+use crate::repair::*;
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+// max passes of swaps of `cluster`
+pub const CLUSTER_PASSES: usize = 100;
+
+// TrackId - an id or a label of a left or of a right
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum TrackId {
+    Id(usize),
+    Label(String),
+}
+
+impl TrackId {
+    // `resolve` the id (`len` ids with `labels`)
+    fn resolve(&self, side: &str, len: usize, labels: &[String]) -> Result<usize, Box<dyn Error>> {
+        match self {
+            TrackId::Id(id) if *id < len => Ok(*id),
+            TrackId::Id(id) => Err(format!("{side}: {id} >= {len} !!!").into()),
+            TrackId::Label(label) => labels
+                .iter()
+                .position(|x| x == label)
+                .ok_or_else(|| format!("{side}: unknown label `{label}` !!!").into()),
+        }
+    }
+}
+
+// SerdeTrack - a track of a JSON file: `{"name": "A", "lefts": [0, "Alice"], "rights": [3, "paper 7"]}`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SerdeTrack {
+    #[serde(default)]
+    pub name: String,
+    pub lefts: Vec<TrackId>,
+    pub rights: Vec<TrackId>,
+}
+
+// Track - sorted ids of the lefts and of the rights of a track
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Track {
+    pub name: String,
+    pub lefts: Vec<usize>,
+    pub rights: Vec<usize>,
+}
+
+// Partition - how the lefts and the rights are split into tracks
+#[derive(Debug, Clone, PartialEq)]
+pub enum Partition {
+    Clusters(usize),         // `N` tracks by `cluster`
+    Tracks(Vec<SerdeTrack>), // `L,L,.../R,R,...;...` ids or labels, or a JSON file of `SerdeTrack`s
+}
+
+impl Partition {
+    // `parse` - `N`, `L,L,.../R,R,...;L,L,.../R,R,...` or the path of a JSON file
+    pub fn parse(spec: &str) -> Result<Self, Box<dyn Error>> {
+        if let Ok(count) = spec.parse() {
+            return Ok(Partition::Clusters(count));
+        }
+        if !spec.contains('/') && !std::path::Path::new(spec).is_file() && spec.contains(',') {
+            return Err(format!(
+                "`{spec}` is not a file and has no `/` (N, LEFTS/RIGHTS;... or a JSON file of tracks) !!!"
+            )
+            .into());
+        }
+        if !spec.contains('/') || std::path::Path::new(spec).is_file() {
+            let json = fs::read_to_string(spec).map_err(|err| format!("{spec}: {err}"))?;
+            let tracks = serde_json::from_str(&json).map_err(|err| format!("{spec}: {err}"))?;
+            return Ok(Partition::Tracks(tracks));
+        }
+        let ids = |list: &str| -> Vec<TrackId> {
+            list.split(',')
+                .filter(|x| !x.is_empty())
+                .map(|x| match x.parse() {
+                    Ok(id) => TrackId::Id(id),
+                    Err(_) => TrackId::Label(x.to_string()),
+                })
+                .collect()
+        };
+        let mut tracks = vec![];
+        for (i, track) in spec.split(';').enumerate() {
+            let (lefts, rights) = track.split_once('/').ok_or_else(|| {
+                format!("bad track `{track}` (LEFTS/RIGHTS of comma separated ids or labels) !!!")
+            })?;
+            tracks.push(SerdeTrack {
+                name: (i + 1).to_string(),
+                lefts: ids(lefts),
+                rights: ids(rights),
+            });
+        }
+        Ok(Partition::Tracks(tracks))
+    }
+
+    // `tracks` of `assignments` (with `labels`): disjoint, not empty, sorted ids
+    pub fn tracks(
+        &self,
+        assignments: &Assignments,
+        labels: &Labels,
+    ) -> Result<Vec<Track>, Box<dyn Error>> {
+        let (_, m, n) = assignments.get_kmn();
+        let tracks = match self {
+            Partition::Clusters(count) => cluster(assignments, *count)?,
+            Partition::Tracks(serde_tracks) => {
+                let mut tracks = vec![];
+                for (i, track) in serde_tracks.iter().enumerate() {
+                    let name = match track.name.is_empty() {
+                        true => (i + 1).to_string(),
+                        false => track.name.clone(),
+                    };
+                    let resolved = |side: &str, ids: &[TrackId], len: usize, labels: &[String]| {
+                        ids.iter()
+                            .map(|x| x.resolve(&format!("track {name}: {side}"), len, labels))
+                            .collect::<Result<Vec<usize>, _>>()
+                    };
+                    let mut lefts = resolved("lefts", &track.lefts, m, &labels.lefts)?;
+                    let mut rights = resolved("rights", &track.rights, n, &labels.rights)?;
+                    lefts.sort();
+                    rights.sort();
+                    tracks.push(Track {
+                        name,
+                        lefts,
+                        rights,
+                    });
+                }
+                tracks
+            }
+        };
+        let (mut left_track, mut right_track) = (vec![None; m], vec![None; n]);
+        for track in &tracks {
+            if track.lefts.is_empty() || track.rights.is_empty() {
+                return Err(format!("track {}: no lefts or no rights !!!", track.name).into());
+            }
+            for (side, ids, of) in [
+                ("left", &track.lefts, &mut left_track),
+                ("right", &track.rights, &mut right_track),
+            ] {
+                for &id in ids {
+                    if let Some(other) = of[id].replace(&track.name) {
+                        return Err(format!(
+                            "{side} {id} in tracks {other} and {} !!!",
+                            track.name
+                        )
+                        .into());
+                    }
+                }
+            }
+        }
+        Ok(tracks)
+    }
+}
+
+// `cluster` - `count` tracks of nearly equal numbers of lefts and of rights with as few forbidden pairs
+// inside the tracks as possible, i.e. dense blocks of the allowed-pair graph: from the lefts and the rights
+// dealt round-robin, swaps of two lefts or two rights of different tracks decreasing the number of forbidden
+// pairs inside the tracks are made until none is left (or `CLUSTER_PASSES` passes)
+pub fn cluster(assignments: &Assignments, count: usize) -> Result<Vec<Track>, Box<dyn Error>> {
+    let (_, m, n) = assignments.get_kmn();
+    if !(1 <= count && count <= m && count <= n) {
+        return Err(
+            format!("cluster: 1 <= {count} <= m = {m}, n = {n} is not satisfied !!!").into(),
+        );
+    }
+    let (mut left_adjacent, mut right_adjacent) = (vec![vec![]; m], vec![vec![]; n]);
+    let mut left_track: Vec<usize> = (0..m).map(|l| l % count).collect();
+    let mut right_track: Vec<usize> = (0..n).map(|r| r % count).collect();
+    // forbidden pairs of each left (right) with the rights (lefts) of each track
+    let (mut of_left, mut of_right) = (vec![vec![0i64; count]; m], vec![vec![0i64; count]; n]);
+    // forbidden pairs outside m x n are not between any ids, so skipped
+    let in_range = |&&(l, r): &&(usize, usize)| l < m && r < n;
+    for &(l, r) in assignments.forbidden().iter().filter(in_range) {
+        left_adjacent[l].push(r);
+        right_adjacent[r].push(l);
+        of_left[l][right_track[r]] += 1;
+        of_right[r][left_track[l]] += 1;
+    }
+    for _ in 0..CLUSTER_PASSES {
+        let improved_lefts = swap_pass(&mut left_track, &of_left, &mut of_right, &left_adjacent);
+        let improved_rights = swap_pass(&mut right_track, &of_right, &mut of_left, &right_adjacent);
+        if !improved_lefts && !improved_rights {
+            break;
+        }
+    }
+    Ok((0..count)
+        .map(|t| Track {
+            name: (t + 1).to_string(),
+            lefts: (0..m).filter(|l| left_track[*l] == t).collect(),
+            rights: (0..n).filter(|r| right_track[*r] == t).collect(),
+        })
+        .collect())
+}
+
+// `swap_pass` - swaps of two ids of one side in different `tracks` decreasing the forbidden pairs inside
+// the tracks (`of` - forbidden pairs of each id with each track, not changed by the swaps of its side,
+// `other_of` - the same of the other side, `adjacent` - forbidden pairs of each id)
+fn swap_pass(
+    tracks: &mut [usize],
+    of: &[Vec<i64>],
+    other_of: &mut [Vec<i64>],
+    adjacent: &[Vec<usize>],
+) -> bool {
+    let mut improved = false;
+    for a in 0..tracks.len() {
+        for b in a + 1..tracks.len() {
+            let (ta, tb) = (tracks[a], tracks[b]);
+            if ta == tb || of[a][tb] - of[a][ta] + of[b][ta] - of[b][tb] >= 0 {
+                continue;
+            }
+            tracks.swap(a, b);
+            for (id, from, to) in [(a, ta, tb), (b, tb, ta)] {
+                for &x in &adjacent[id] {
+                    other_of[x][from] -= 1;
+                    other_of[x][to] += 1;
+                }
+            }
+            improved = true;
+        }
+    }
+    improved
+}
+
+// TrackResult - the solved track with the original ids
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TrackResult {
+    pub track: Track,
+    pub k: usize,
+    pub m: usize,
+    pub n: usize,
+    pub p: usize,
+    pub pairs: Vec<(usize, usize)>,
+    pub forbidden: Vec<(usize, usize)>, // forbidden pairs used
+}
+
+impl fmt::Display for TrackResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "track {}: (k,m,n) = {:?} [p = {}], {} pairs, {} forbidden, lefts {:?}, rights {:?}",
+            self.track.name,
+            (self.k, self.m, self.n),
+            self.p,
+            self.pairs.len(),
+            self.forbidden.len(),
+            self.track.lefts,
+            self.track.rights
+        )
+    }
+}
+
+// Split - the solved tracks and the lefts and the rights of no track
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Split {
+    pub tracks: Vec<TrackResult>,
+    pub unassigned_lefts: Vec<usize>,
+    pub unassigned_rights: Vec<usize>,
+}
+
+impl fmt::Display for Split {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for track in &self.tracks {
+            writeln!(f, "{track}")?;
+        }
+        if !self.unassigned_lefts.is_empty() || !self.unassigned_rights.is_empty() {
+            writeln!(
+                f,
+                "#WARNING# In no track: lefts {:?}, rights {:?}",
+                self.unassigned_lefts, self.unassigned_rights
+            )?;
+        }
+        write!(
+            f,
+            "{} tracks, {} pairs, {} forbidden",
+            self.tracks.len(),
+            self.pairs().len(),
+            self.forbidden()
+        )
+    }
+}
+
+impl Split {
+    // combined sorted pairs of all the tracks
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        let mut out: Vec<(usize, usize)> =
+            self.tracks.iter().flat_map(|x| x.pairs.clone()).collect();
+        out.sort();
+        out
+    }
+
+    // number of forbidden pairs used by all the tracks
+    pub fn forbidden(&self) -> usize {
+        self.tracks.iter().map(|x| x.forbidden.len()).sum()
+    }
+
+    // `body` - combined pairs formatted as `Assignments::assignments_body`
+    pub fn body(&self) -> String {
+        let forbidden: Vec<(usize, usize)> = self
+            .tracks
+            .iter()
+            .flat_map(|x| x.forbidden.clone())
+            .collect();
+        let mut out = String::from("  [\n");
+        for pair in self.pairs() {
+            let warn = if forbidden.contains(&pair) {
+                " !!!"
+            } else {
+                ""
+            };
+            out = format!("{}    {} {}{}\n", out, pair.0, pair.1, warn);
+        }
+        out = format!("{}  ]\n", out);
+        out
+    }
+
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+impl Assignments {
+    // `solved_track` - the `track` reduced with `k` (the current one if None; with p = ceil(k * n / m)
+    // for a track of more lefts than rights) and solved with the fewest forbidden pairs keeping as many
+    // current pairs of the track as possible (see `repair::repair_pairs`)
+    pub fn solved_track(
+        &self,
+        track: &Track,
+        k: Option<usize>,
+    ) -> Result<TrackResult, Box<dyn Error>> {
+        let k = k.unwrap_or(self.k);
+        let (m, n) = (track.lefts.len(), track.rights.len());
+        let reduced = match m <= n {
+            true => self.reduced_to(&track.lefts, &track.rights, Some(k), None),
+            false => self.reduced_to(&track.lefts, &track.rights, None, Some((k * n).div_ceil(m))),
+        }
+        .map_err(|err| format!("track {}: {}", track.name, err.to_string().trim_end()))?;
+        let local = |(l, r): &(usize, usize)| {
+            Some((
+                track.lefts.binary_search(l).ok()?,
+                track.rights.binary_search(r).ok()?,
+            ))
+        };
+        let baseline: Vec<(usize, usize)> =
+            self.get_pairs_of_ids().iter().filter_map(local).collect();
+        let p = reduced.p();
+        let pairs =
+            repair_pairs(reduced.k, m, n, p, &baseline, &reduced.forbidden).ok_or_else(|| {
+                format!(
+                    "track {}: no assignments satisfy the degree rules !!!",
+                    track.name
+                )
+            })?;
+        let original: Vec<(usize, usize)> = pairs
+            .iter()
+            .map(|(l, r)| (track.lefts[*l], track.rights[*r]))
+            .collect();
+        Ok(TrackResult {
+            track: track.clone(),
+            k: reduced.k,
+            m,
+            n,
+            p,
+            forbidden: original
+                .iter()
+                .filter(|x| self.forbidden.contains(x))
+                .copied()
+                .collect(),
+            pairs: original,
+        })
+    }
+
+    // `split` into the tracks of `partition` (ids or `labels`), each solved by `solved_track`
+    pub fn split(
+        &self,
+        partition: &Partition,
+        labels: &Labels,
+        k: Option<usize>,
+    ) -> Result<Split, Box<dyn Error>> {
+        let tracks = partition.tracks(self, labels)?;
+        let mut split = Split {
+            unassigned_lefts: (0..self.m)
+                .filter(|l| !tracks.iter().any(|x| x.lefts.contains(l)))
+                .collect(),
+            unassigned_rights: (0..self.n)
+                .filter(|r| !tracks.iter().any(|x| x.rights.contains(r)))
+                .collect(),
+            ..Split::default()
+        };
+        for track in &tracks {
+            split.tracks.push(self.solved_track(track, k)?);
+        }
+        Ok(split)
+    }
+}
@@ -0,0 +1,474 @@
+// Pluggable search strategies: object-safe `SearchStrategy`, the search driver and the registry
+
+use crate::cmd::*;
+use crate::portfolio::*;
+use crate::progress::*;
+use crate::*;
+use rand::RngCore;
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+
+// SearchStrategy - stateful search making one `step` at a time (see `run_search`)
+pub trait SearchStrategy {
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    // current values of the parameters: (name, value)
+    fn parameters(&self) -> Vec<(String, String)>;
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), Box<dyn Error>>;
+
+    // called once before the steps
+    fn init(
+        &mut self,
+        _assignments: &mut Assignments,
+        _rng: &mut dyn RngCore,
+    ) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    // one step of the search changing `assignments`
+    fn step(
+        &mut self,
+        assignments: &mut Assignments,
+        rng: &mut dyn RngCore,
+    ) -> Result<(), Box<dyn Error>>;
+
+    // name of the move done by the last step (e.g. for the rates of moves in `trace::Trace`)
+    fn last_move(&self) -> &str {
+        self.name()
+    }
+
+    // statistics of the steps done (printed after the search)
+    fn report(&self) -> String {
+        String::new()
+    }
+}
+
+// `set_parameters` sets `name=value` arguments of `strategy`
+pub fn set_parameters(
+    strategy: &mut dyn SearchStrategy,
+    args: &[(String, String)],
+) -> Result<(), Box<dyn Error>> {
+    for (name, value) in args {
+        if !strategy.parameters().iter().any(|(x, _)| x == name) {
+            return Err(format!(
+                "{}: unknown parameter `{name}` (parameters: {}) !!!",
+                strategy.name(),
+                parameters_line(strategy)
+            )
+            .into());
+        }
+        strategy
+            .set_parameter(name, value)
+            .map_err(|err| format!("{}: {name}={value}: {err}", strategy.name()))?;
+    }
+    Ok(())
+}
+
+// `parameters_line` - e.g. `side=lr l_percent=50`
+pub fn parameters_line(strategy: &dyn SearchStrategy) -> String {
+    strategy
+        .parameters()
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// `split_name_values` splits `name=value` arguments
+pub fn split_name_values(args: &[&str]) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut out = vec![];
+    for arg in args {
+        let Some((name, value)) = arg.split_once('=') else {
+            return Err(format!("`{arg}` is not of the form name=value !!!").into());
+        };
+        out.push((name.to_string(), value.to_string()));
+    }
+    Ok(out)
+}
+
+// SearchOutcome
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchOutcome {
+    pub steps: usize,
+    pub forbidden: usize, // forbidden pairs in the best assignments found (the backup)
+    pub stop: Stop,
+    pub report: String, // `SearchStrategy::report`
+}
+
+// `run_search` - steps of `strategy` within `budget` (stopped also by Ctrl-C or `progress`)
+// keeping the backup and the solution pool; it ends in the best state found (also when
+// a step of `strategy` fails)
+pub fn run_search(
+    strategy: &mut dyn SearchStrategy,
+    assignments: &mut Assignments,
+    budget: Budget,
+    rng: &mut dyn RngCore,
+    progress: &mut dyn SearchProgress,
+) -> Result<SearchOutcome, Box<dyn Error>> {
+    let _guard = SearchGuard::new(); // Ctrl-C interrupts the search
+    let start = Instant::now();
+    let mut last_report = start;
+
+    // have backup before we start
+    let mut f_min = assignments.f_min_backup_update(assignments.get_pairs_of_ids());
+    let current_pairs = assignments.get_pairs_of_ids();
+    assignments.pool_offer(&current_pairs, assignments.number_of_forbidden_used());
+    let mut failed = strategy.init(assignments, rng).err();
+    let mut state = Progress {
+        steps: 0,
+        forbidden: assignments.number_of_forbidden_used(),
+        best: f_min,
+        elapsed: Default::default(),
+    };
+    progress.on_start(&state);
+    // no assignments have less forbidden pairs
+    let bound = assignments.forbidden_minimum();
+    let mut stop = Stop::Steps;
+    for step in 1..=budget.max {
+        if failed.is_some() {
+            break;
+        }
+        if let Err(err) = strategy.step(assignments, rng) {
+            failed = Some(err);
+            break;
+        }
+        let current_pairs = assignments.get_pairs_of_ids();
+        let f = intersection_size(&current_pairs, &assignments.forbidden);
+        // here: `f_min` is actual for current backup
+        if f < f_min {
+            f_min = f;
+            // the pool is offered only the improvements (and the final state)
+            assignments.pool_offer(&current_pairs, f);
+            assignments.f_min_backup = Some(current_pairs); // fearless overwrite ! ;-)
+        }
+        let now = Instant::now();
+        state = Progress {
+            steps: step,
+            forbidden: f,
+            best: f_min,
+            elapsed: now - start,
+        };
+        progress.on_step(&state, strategy.last_move());
+        if f == 0 {
+            // We have zero forbidden !!!
+            stop = Stop::Solved;
+            break;
+        }
+        if f <= bound {
+            stop = Stop::Bound;
+            break;
+        }
+        if now - last_report >= progress.report_interval() {
+            last_report = now;
+            progress.on_report(&state);
+        }
+        if interrupted() {
+            stop = Stop::Interrupted;
+        } else if budget.time.is_some_and(|time| state.elapsed >= time) {
+            stop = Stop::Time;
+        } else if progress.should_stop(&state) {
+            stop = Stop::Requested;
+        } else {
+            continue;
+        }
+        break;
+    }
+    if failed.is_some() {
+        stop = Stop::Failed;
+        state.elapsed = start.elapsed();
+    }
+    // the search ends in the best state found (the last step need not be the best)
+    if f_min < assignments.number_of_forbidden_used() {
+        assignments.f_min_backup_restore();
+    }
+    let current_pairs = assignments.get_pairs_of_ids();
+    assignments.pool_offer(&current_pairs, assignments.number_of_forbidden_used());
+    state.best = f_min;
+    progress.on_stop(&state, stop);
+    if let Some(err) = failed {
+        return Err(format!("{}: {err}", strategy.name()).into());
+    }
+    Ok(SearchOutcome {
+        steps: state.steps,
+        forbidden: if stop == Stop::Solved { 0 } else { f_min },
+        stop,
+        report: strategy.report(),
+    })
+}
+
+// `parse_side` parses `l`, `r` or `lr` (`lr` with `l_percent`)
+fn parse_side(value: &str, l_percent: usize) -> Result<Side, Box<dyn Error>> {
+    match value {
+        "l" => Ok(Side::Left),
+        "r" => Ok(Side::Right),
+        "lr" => Ok(Side::LeftPercent(l_percent)),
+        _ => Err(format!("side `{value}` is not one of l, r, lr !!!").into()),
+    }
+}
+
+// SideStrategy - `SideSearch` actions (`Permute`, `Swap`, `BackSwap`) as `SearchStrategy`
+pub struct SideStrategy<S: SideSearch> {
+    pub side: Side,
+    pub l_steps: usize,
+    pub r_steps: usize,
+    last_move: &'static str,
+    search: std::marker::PhantomData<S>,
+}
+
+impl<S: SideSearch> SideStrategy<S> {
+    pub fn new(side: Side) -> Self {
+        Self {
+            side,
+            l_steps: 0,
+            r_steps: 0,
+            last_move: "",
+            search: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<S: SideSearch> SearchStrategy for SideStrategy<S> {
+    fn name(&self) -> &str {
+        S::NAME
+    }
+
+    fn description(&self) -> &str {
+        S::DESCRIPTION
+    }
+
+    fn parameters(&self) -> Vec<(String, String)> {
+        let (side, l_percent) = match self.side {
+            Side::Left => ("l", 50),
+            Side::Right => ("r", 50),
+            Side::LeftPercent(l_percent) => ("lr", l_percent),
+        };
+        vec![
+            (String::from("side"), side.to_string()),
+            (String::from("l_percent"), l_percent.to_string()),
+        ]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        match name {
+            "side" => {
+                let l_percent = match self.side {
+                    Side::LeftPercent(l_percent) => l_percent,
+                    _ => 50,
+                };
+                self.side = parse_side(value, l_percent)?;
+            }
+            "l_percent" => {
+                let l_percent: usize = value.parse()?;
+                if l_percent > 100 {
+                    return Err(format!("{l_percent} > 100 !!!").into());
+                }
+                self.side = Side::LeftPercent(l_percent);
+            }
+            _ => return Err(format!("unknown parameter `{name}` !!!").into()),
+        }
+        Ok(())
+    }
+
+    fn step(
+        &mut self,
+        assignments: &mut Assignments,
+        mut rng: &mut dyn RngCore,
+    ) -> Result<(), Box<dyn Error>> {
+        match self.side {
+            Side::Left => {
+                S::action_left(assignments, &mut rng);
+                self.l_steps += 1;
+                self.last_move = "left";
+            }
+            Side::Right => {
+                S::action_right(assignments, &mut rng);
+                self.r_steps += 1;
+                self.last_move = "right";
+            }
+            Side::LeftPercent(l_p) => {
+                if rng.random_range(0..100) < l_p {
+                    S::action_left(assignments, &mut rng);
+                    self.l_steps += 1;
+                    self.last_move = "left";
+                } else {
+                    S::action_right(assignments, &mut rng);
+                    self.r_steps += 1;
+                    self.last_move = "right";
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn last_move(&self) -> &str {
+        self.last_move
+    }
+}
+
+// SwitchEndpoints - 'skeleton breaking' by `Assignments::try_switching_endpoints`
+// (result may be not isomorphic)
+pub struct SwitchEndpoints();
+
+impl SearchStrategy for SwitchEndpoints {
+    fn name(&self) -> &str {
+        "switch"
+    }
+
+    fn description(&self) -> &str {
+        "cross-switching of the pairs with forbidden (the same as `sbrk`)"
+    }
+
+    fn parameters(&self) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn set_parameter(&mut self, name: &str, _value: &str) -> Result<(), Box<dyn Error>> {
+        Err(format!("unknown parameter `{name}` !!!").into())
+    }
+
+    fn step(
+        &mut self,
+        assignments: &mut Assignments,
+        _rng: &mut dyn RngCore,
+    ) -> Result<(), Box<dyn Error>> {
+        let tmp = assignments.get_pairs_of_ids();
+        let pairs = assignments.try_switching_endpoints(tmp.clone())?;
+        assignments.set_pairs_of_ids(&pairs);
+        if let Err(err) = assignments.test_assignments() {
+            assignments.set_pairs_of_ids(&tmp);
+            return Err(err);
+        }
+        Ok(())
+    }
+}
+
+// StrategyFactory - creates a strategy with default parameters
+pub type StrategyFactory = fn() -> Box<dyn SearchStrategy>;
+
+// StrategyRegistry - strategies available by name
+pub struct StrategyRegistry {
+    factories: Vec<StrategyFactory>,
+}
+
+impl StrategyRegistry {
+    pub fn new() -> Self {
+        Self { factories: vec![] }
+    }
+
+    // registry of the strategies of this crate
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(|| Box::new(SideStrategy::<Permute>::new(Side::LeftPercent(50))));
+        registry.register(|| Box::new(SideStrategy::<Swap>::new(Side::LeftPercent(50))));
+        registry.register(|| Box::new(SideStrategy::<BackSwap>::new(Side::LeftPercent(50))));
+        registry.register(|| Box::new(SwitchEndpoints()));
+        registry.register(|| Box::new(Portfolio::new()));
+        registry
+    }
+
+    // `register` adds (or replaces the strategy with the same name created by) `factory`
+    pub fn register(&mut self, factory: StrategyFactory) {
+        let name = factory().name().to_string();
+        self.factories.retain(|x| x().name() != name);
+        self.factories.push(factory);
+    }
+
+    pub fn create(&self, name: &str) -> Option<Box<dyn SearchStrategy>> {
+        self.factories
+            .iter()
+            .map(|factory| factory())
+            .find(|x| x.name() == name)
+    }
+
+    // new instances of all strategies
+    pub fn strategies(&self) -> Vec<Box<dyn SearchStrategy>> {
+        self.factories.iter().map(|factory| factory()).collect()
+    }
+
+    // `help` - one line for each strategy with its default parameters
+    pub fn help(&self) -> String {
+        let mut out = String::new();
+        for strategy in self.strategies() {
+            out = format!(
+                "{}    {:<10} {} [{}]\n",
+                out,
+                strategy.name(),
+                strategy.description(),
+                parameters_line(&*strategy)
+            );
+        }
+        out
+    }
+}
+
+impl Default for StrategyRegistry {
+    fn default() -> Self {
+        Self::with_builtin()
+    }
+}
+
+static REGISTRY: LazyLock<Mutex<StrategyRegistry>> =
+    LazyLock::new(|| Mutex::new(StrategyRegistry::with_builtin()));
+
+// `register_strategy` makes the strategy created by `factory` available
+// in the menu and the CLI (`search <strategy> name=value ...`)
+pub fn register_strategy(factory: StrategyFactory) {
+    REGISTRY
+        .lock()
+        .expect("strategy registry poisoned")
+        .register(factory);
+}
+
+// `with_registry` runs `f` on the global registry
+pub fn with_registry<T, F: FnOnce(&StrategyRegistry) -> T>(f: F) -> T {
+    f(&REGISTRY.lock().expect("strategy registry poisoned"))
+}
+
+// `search_by_name` - `run_search` with the strategy `name` from the global registry
+// and `args`: its parameters and `max=MAX` and `time=DURATION` of the budget
+pub fn search_by_name(
+    name: &str,
+    args: &[(String, String)],
+    assignments: &mut Assignments,
+    rng: &mut dyn RngCore,
+    progress: &mut dyn SearchProgress,
+) -> Result<SearchOutcome, Box<dyn Error>> {
+    let Some(mut strategy) = with_registry(|registry| registry.create(name)) else {
+        let names = with_registry(|registry| {
+            registry
+                .strategies()
+                .iter()
+                .map(|x| x.name().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        });
+        return Err(format!("unknown strategy `{name}` (strategies: {names}) !!!").into());
+    };
+    let mut budget = Budget::steps(0);
+    let mut max = None;
+    let mut parameters = vec![];
+    for (arg_name, value) in args {
+        match arg_name.as_str() {
+            "max" => {
+                max = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|err| format!("max={value}: {err}"))?,
+                )
+            }
+            "time" => budget.time = Some(parse_duration(value)?),
+            _ => parameters.push((arg_name.clone(), value.clone())),
+        }
+    }
+    // with `time` the search may run until the time budget is exhausted
+    budget.max = match (max, budget.time) {
+        (Some(max), _) => max,
+        (None, Some(_)) => usize::MAX,
+        (None, None) => return Err("expected max=MAX and/or time=DURATION !!!".into()),
+    };
+    set_parameters(&mut *strategy, &parameters)?;
+    run_search(&mut *strategy, assignments, budget, rng, progress)
+}
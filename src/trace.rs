@@ -0,0 +1,278 @@
+// Trace of a search run: forbidden over steps, new bests, rates of moves, ASCII chart
+
+use crate::progress::*;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+
+// max number of points kept (the sampling interval doubles when exceeded)
+pub const TRACE_POINTS: usize = 1000;
+
+// TracePoint
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct TracePoint {
+    pub step: usize,
+    pub forbidden: usize,
+    pub best: usize,
+    pub elapsed: f64, // seconds
+}
+
+impl From<&Progress> for TracePoint {
+    fn from(progress: &Progress) -> Self {
+        Self {
+            step: progress.steps,
+            forbidden: progress.forbidden,
+            best: progress.best,
+            elapsed: progress.elapsed.as_secs_f64(),
+        }
+    }
+}
+
+// MoveRates - counts of the steps of one move type
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MoveRates {
+    pub name: String,
+    pub used: usize,
+    pub improved: usize, // forbidden decreased
+    pub worsened: usize, // forbidden increased (the other steps are neutral)
+}
+
+// no strategy rejects moves (e.g. `BackSwap` undoes worsening swaps inside its step), so the rates
+// are of the effect of the steps on forbidden, not of acceptance
+impl MoveRates {
+    pub fn neutral(&self) -> usize {
+        self.used - self.improved - self.worsened
+    }
+
+    // rate of the steps not increasing forbidden
+    pub fn non_worsening(&self) -> f64 {
+        if self.used == 0 {
+            return 0.0;
+        }
+        (self.used - self.worsened) as f64 / self.used as f64
+    }
+}
+
+// Trace
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Trace {
+    pub strategy: String,
+    pub steps: usize,
+    pub elapsed: f64, // seconds
+    pub stop: String,
+    pub interval: usize,         // sampling interval of `points`
+    pub points: Vec<TracePoint>, // sampled steps (with the start at step 0)
+    pub bests: Vec<TracePoint>,  // steps finding a new best
+    pub moves: Vec<MoveRates>,
+    #[serde(skip)]
+    last: Option<TracePoint>,
+}
+
+impl Trace {
+    pub fn new(strategy: &str) -> Self {
+        Self {
+            strategy: strategy.to_string(),
+            interval: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn start(&mut self, progress: &Progress) {
+        let point = TracePoint::from(progress);
+        self.points.push(point);
+        self.last = Some(point);
+    }
+
+    pub fn step(&mut self, progress: &Progress, movement: &str) {
+        let point = TracePoint::from(progress);
+        let last = self.last.unwrap_or(point);
+        let rates = match self.moves.iter_mut().find(|x| x.name == movement) {
+            Some(rates) => rates,
+            None => {
+                self.moves.push(MoveRates {
+                    name: movement.to_string(),
+                    used: 0,
+                    improved: 0,
+                    worsened: 0,
+                });
+                self.moves.last_mut().expect("just pushed")
+            }
+        };
+        rates.used += 1;
+        if point.forbidden < last.forbidden {
+            rates.improved += 1;
+        } else if point.forbidden > last.forbidden {
+            rates.worsened += 1;
+        }
+        if point.best < last.best {
+            self.bests.push(point);
+        }
+        if point.step % self.interval == 0 {
+            self.points.push(point);
+            if self.points.len() > TRACE_POINTS {
+                // keep the start and every other point
+                self.interval *= 2;
+                let interval = self.interval;
+                self.points.retain(|x| x.step % interval == 0);
+            }
+        }
+        self.last = Some(point);
+    }
+
+    pub fn finish(&mut self, progress: &Progress, stop: Stop) {
+        self.steps = progress.steps;
+        self.elapsed = progress.elapsed.as_secs_f64();
+        self.stop = stop.to_string();
+        let point = TracePoint::from(progress);
+        if self.points.last().is_none_or(|x| x.step != point.step) {
+            self.points.push(point);
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    // `to_csv` - the points as `step,forbidden,best,elapsed` lines
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("step,forbidden,best,elapsed\n");
+        for x in &self.points {
+            out = format!(
+                "{}{},{},{},{:.6}\n",
+                out, x.step, x.forbidden, x.best, x.elapsed
+            );
+        }
+        out
+    }
+
+    // `write_file` writes JSON to `path` ending with `.json` and CSV otherwise
+    pub fn write_file(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let out = if path.ends_with(".json") {
+            self.to_json()? + "\n"
+        } else {
+            self.to_csv()
+        };
+        fs::write(path, out)?;
+        Ok(())
+    }
+
+    // `summary` - steps, time, new bests and rates of moves
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        let steps_per_sec = if self.elapsed > 0.0 {
+            self.steps as f64 / self.elapsed
+        } else {
+            0.0
+        };
+        let _ = writeln!(
+            out,
+            "{}: {} steps ({}), {:.2}s, {:.0} steps/s",
+            self.strategy, self.steps, self.stop, self.elapsed, steps_per_sec
+        );
+        let bests: Vec<String> = self
+            .bests
+            .iter()
+            .map(|x| format!("{} at step {}", x.best, x.step))
+            .collect();
+        let _ = writeln!(out, "  new bests: [{}]", bests.join(", "));
+        for x in &self.moves {
+            let rate = |count: usize| 100.0 * count as f64 / x.used.max(1) as f64;
+            let _ = writeln!(
+                out,
+                "  move {:<8} used {:>8}, improved {:>5.1}%, neutral {:>5.1}%, worsened {:>5.1}%",
+                x.name,
+                x.used,
+                rate(x.improved),
+                rate(x.neutral()),
+                rate(x.worsened)
+            );
+        }
+        out
+    }
+
+    // `chart` - forbidden (`*`) and best (`.`) over steps in `width` x `height` characters
+    pub fn chart(&self, width: usize, height: usize) -> String {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return String::from("(empty trace)\n");
+        };
+        let (width, height) = (width.max(2), height.max(2));
+        let max = self.points.iter().map(|x| x.forbidden).max().unwrap_or(0);
+        let min = self.points.iter().map(|x| x.best).min().unwrap_or(0);
+        let span = (last.step - first.step).max(1);
+        let row = |f: usize| {
+            if max == min {
+                0
+            } else {
+                (max - f) * (height - 1) / (max - min)
+            }
+        };
+        let mut grid = vec![vec![' '; width]; height];
+        for x in &self.points {
+            let column = (x.step - first.step) * (width - 1) / span;
+            grid[row(x.best)][column] = '.';
+            grid[row(x.forbidden)][column] = '*';
+        }
+        let mut out = String::new();
+        for (i, line) in grid.iter().enumerate() {
+            let label = if i == 0 {
+                format!("{max:>5}")
+            } else if i == height - 1 {
+                format!("{min:>5}")
+            } else {
+                String::from("     ")
+            };
+            let _ = writeln!(out, "{label} |{}", line.iter().collect::<String>());
+        }
+        let _ = writeln!(out, "      +{}", "-".repeat(width));
+        let _ = writeln!(
+            out,
+            "       step {:<w$}{}",
+            first.step,
+            last.step,
+            w = width.saturating_sub(12)
+        );
+        out
+    }
+}
+
+// Traced - `inner` progress with the trace of the search (if `trace` is set)
+pub struct Traced<P: SearchProgress> {
+    pub inner: P,
+    pub trace: Option<Trace>,
+}
+
+impl<P: SearchProgress> SearchProgress for Traced<P> {
+    fn on_start(&mut self, progress: &Progress) {
+        if let Some(trace) = &mut self.trace {
+            trace.start(progress);
+        }
+        self.inner.on_start(progress);
+    }
+
+    fn on_step(&mut self, progress: &Progress, movement: &str) {
+        if let Some(trace) = &mut self.trace {
+            trace.step(progress, movement);
+        }
+        self.inner.on_step(progress, movement);
+    }
+
+    fn on_report(&mut self, progress: &Progress) {
+        self.inner.on_report(progress);
+    }
+
+    fn should_stop(&mut self, progress: &Progress) -> bool {
+        self.inner.should_stop(progress)
+    }
+
+    fn on_stop(&mut self, progress: &Progress, stop: Stop) {
+        if let Some(trace) = &mut self.trace {
+            trace.finish(progress, stop);
+        }
+        self.inner.on_stop(progress, stop);
+    }
+
+    fn report_interval(&self) -> std::time::Duration {
+        self.inner.report_interval()
+    }
+}